@@ -0,0 +1,258 @@
+//! Adaptive difficulty nudges the speed and obstacle spacing of a run towards what the player can
+//! handle, based on how quickly and accurately they've been typing.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{difficulty::ActiveDifficulty, typing::Keystroke, AppState, Speed};
+
+/// How far back keystrokes count towards the player's current typing speed.
+const WINDOW_SECS: f32 = 10.;
+/// Adaptation doesn't kick in until there's enough typing to go on.
+const WARMUP_SECS: f32 = 5.;
+/// The typing speed at which the preset is left as it is.
+const REFERENCE_WPM: f32 = 40.;
+/// Each typo counts against the player's skill this many times over.
+const ERROR_PENALTY: f32 = 2.;
+/// The most the speed can be raised or lowered, as a fraction of the preset's speed.
+const MAX_ADJUSTMENT: f32 = 0.3;
+/// How quickly the adjustment follows the player's skill, per second.
+const ADJUSTMENT_RATE: f32 = 0.05;
+
+pub struct AdaptivePlugin;
+
+/// Whether adaptive difficulty is turned on in the settings.
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AdaptiveDifficulty(pub bool);
+
+/// Typing statistics for the current run.
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub elapsed: f32,
+    pub correct: u32,
+    pub errors: u32,
+    /// Recent keystrokes, with the time they were typed and whether they were correct.
+    recent: VecDeque<(f32, bool)>,
+    /// How much speed adaptation has added, so that it can be adjusted later.
+    applied_speed: f32,
+}
+impl RunStats {
+    pub fn wpm(&self) -> f32 {
+        wpm(self.correct, self.elapsed)
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        let total = self.correct + self.errors;
+        if total == 0 {
+            return 1.;
+        }
+
+        self.correct as f32 / total as f32
+    }
+
+    /// The typing speed over the last [`WINDOW_SECS`].
+    fn recent_wpm(&self) -> f32 {
+        let correct = self.recent.iter().filter(|(_, correct)| *correct).count();
+        wpm(correct as u32, self.elapsed.min(WINDOW_SECS))
+    }
+
+    /// The fraction of keystrokes over the last [`WINDOW_SECS`] that were typos.
+    fn recent_error_rate(&self) -> f32 {
+        if self.recent.is_empty() {
+            return 0.;
+        }
+
+        let errors = self.recent.iter().filter(|(_, correct)| !*correct).count();
+        errors as f32 / self.recent.len() as f32
+    }
+
+    /// Moves the run on by `secs`, counting the keystrokes typed in the meantime and forgetting
+    /// the ones that are no longer recent.
+    fn record(&mut self, secs: f32, keystrokes: impl IntoIterator<Item = bool>) {
+        self.elapsed += secs;
+        let now = self.elapsed;
+
+        for correct in keystrokes {
+            if correct {
+                self.correct += 1;
+            } else {
+                self.errors += 1;
+            }
+
+            self.recent.push_back((now, correct));
+        }
+
+        while self
+            .recent
+            .front()
+            .is_some_and(|(time, _)| *time < now - WINDOW_SECS)
+        {
+            self.recent.pop_front();
+        }
+    }
+}
+
+fn wpm(correct: u32, secs: f32) -> f32 {
+    if secs <= 0. {
+        return 0.;
+    }
+
+    // A word is conventionally five characters.
+    correct as f32 / 5. / (secs / 60.)
+}
+
+impl Plugin for AdaptivePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>();
+        app.add_systems(OnExit(AppState::StartScreen), reset_stats);
+        app.add_systems(
+            Update,
+            (record_keystrokes, adapt.after(record_keystrokes)).run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+fn reset_stats(mut commands: Commands) {
+    commands.insert_resource(RunStats::default());
+}
+
+fn record_keystrokes(
+    mut keystrokes: MessageReader<Keystroke>,
+    mut stats: ResMut<RunStats>,
+    time: Res<Time>,
+) {
+    stats.record(
+        time.delta_secs(),
+        keystrokes.read().map(|keystroke| keystroke.correct),
+    );
+}
+
+/// Raises the speed and tightens the spacing for players who are typing quickly and accurately,
+/// and eases off for players who are struggling.
+fn adapt(
+    mut stats: ResMut<RunStats>,
+    mut difficulty: ResMut<ActiveDifficulty>,
+    mut speed: ResMut<Speed>,
+    time: Res<Time>,
+) {
+    if !difficulty.adaptive || stats.elapsed < WARMUP_SECS {
+        return;
+    }
+
+    let skill = stats.recent_wpm() / REFERENCE_WPM
+        * (1. - stats.recent_error_rate() * ERROR_PENALTY).max(0.);
+    let target = (skill - 1.).clamp(-MAX_ADJUSTMENT, MAX_ADJUSTMENT);
+
+    let step = ADJUSTMENT_RATE * time.delta_secs();
+    difficulty.adjustment += (target - difficulty.adjustment).clamp(-step, step);
+
+    let extra_speed = difficulty.adjustment * difficulty.preset.speed_at(difficulty.obstacles);
+    let delta = extra_speed - stats.applied_speed;
+    speed.current += delta;
+    speed.max += delta;
+    stats.applied_speed = extra_speed;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::difficulty::{Difficulty, DifficultyPreset, DifficultyPresets};
+
+    #[test]
+    fn wpm_counts_five_characters_as_a_word() {
+        assert_eq!(wpm(50, 60.), 10.);
+        assert_eq!(wpm(25, 15.), 20.);
+    }
+
+    #[test]
+    fn wpm_is_zero_without_time_or_keystrokes() {
+        assert_eq!(wpm(10, 0.), 0.);
+        assert_eq!(wpm(0, 30.), 0.);
+
+        let stats = RunStats::default();
+        assert_eq!(stats.wpm(), 0.);
+        assert_eq!(stats.recent_wpm(), 0.);
+    }
+
+    #[test]
+    fn accuracy_is_perfect_without_keystrokes() {
+        let mut stats = RunStats::default();
+        assert_eq!(stats.accuracy(), 1.);
+        assert_eq!(stats.recent_error_rate(), 0.);
+
+        stats.record(1., [true, false, true, true]);
+        assert_eq!(stats.accuracy(), 0.75);
+        assert_eq!(stats.recent_error_rate(), 0.25);
+    }
+
+    #[test]
+    fn recent_wpm_only_counts_the_window() {
+        let mut stats = RunStats::default();
+
+        // Early on, the window is only as long as the run so far.
+        stats.record(2., [true; 10]);
+        assert!((stats.recent_wpm() - 60.).abs() < 1e-3);
+
+        stats.record(WINDOW_SECS + 1., [true; 5]);
+        assert_eq!(stats.recent.len(), 5);
+        assert!((stats.recent_wpm() - 6.).abs() < 1e-3);
+        assert_eq!(stats.correct, 15);
+
+        stats.record(WINDOW_SECS + 1., []);
+        assert_eq!(stats.recent_wpm(), 0.);
+        assert_eq!(stats.recent_error_rate(), 0.);
+        assert!(stats.wpm() > 0.);
+    }
+
+    /// Runs adaptation for a minute of typing that's all correct or all typos, returning the speed
+    /// it ends up at.
+    fn adapt_for_a_minute(correct: bool) -> (Speed, DifficultyPreset) {
+        let preset = DifficultyPresets::default().get(Difficulty::Normal).clone();
+
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs(1));
+        world.insert_resource(time);
+        world.insert_resource(Speed {
+            current: preset.start_speed,
+            max: preset.max_speed,
+        });
+        world.insert_resource(ActiveDifficulty {
+            preset: preset.clone(),
+            obstacles: 0,
+            adaptive: true,
+            adjustment: 0.,
+        });
+        world.insert_resource(RunStats::default());
+
+        for _ in 0..60 {
+            world.resource_mut::<RunStats>().record(1., [correct; 20]);
+            world.run_system_once(adapt).unwrap();
+        }
+
+        (world.remove_resource::<Speed>().unwrap(), preset)
+    }
+
+    #[test]
+    fn adapt_speeds_up_no_further_than_the_max_adjustment() {
+        let (speed, preset) = adapt_for_a_minute(true);
+        let extra = preset.start_speed * MAX_ADJUSTMENT;
+
+        assert!((speed.current - (preset.start_speed + extra)).abs() < 1e-4);
+        assert!((speed.max - (preset.max_speed + extra)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn adapt_slows_down_no_further_than_the_max_adjustment() {
+        let (speed, preset) = adapt_for_a_minute(false);
+        let extra = preset.start_speed * MAX_ADJUSTMENT;
+
+        assert!((speed.current - (preset.start_speed - extra)).abs() < 1e-4);
+        assert!((speed.max - (preset.max_speed - extra)).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,363 @@
+use bevy::{audio::Volume, ecs::system::SystemParam, prelude::*, transform::TransformSystems};
+use rand::prelude::*;
+
+use crate::{
+    difficulty::ActiveDifficulty, loading::AudioAssets, typing::TypingTarget, Action, AppState,
+    MusicController, Speed,
+};
+
+/// How long it takes for one song to fade into the next.
+const CROSSFADE_SECS: f32 = 1.5;
+
+/// How much faster the game music plays at top speed.
+const MAX_TEMPO_INCREASE: f32 = 0.15;
+/// How far the pitch of each keystroke may stray from the original sample.
+const KEYSTROKE_PITCH_JITTER: f32 = 0.08;
+/// Samples of different keys that make up the click pack, which are only loaded when the click
+/// pack is selected in the settings.
+const CLICK_PACK: [&str; 4] = [
+    "clicks/click_1.ogg",
+    "clicks/click_2.ogg",
+    "clicks/click_3.ogg",
+    "clicks/click_4.ogg",
+];
+/// When the birb crashes, the game music winds down to this speed like a tape being stopped.
+const TAPE_STOP_SPEED: f32 = 0.4;
+
+/// Volume levels go from silent at 0 to full volume at `MAX_VOLUME`.
+pub const MAX_VOLUME: u8 = 10;
+
+pub struct AudioMixPlugin;
+
+/// Every sound belongs to a category that has its own volume and mute state.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AudioCategory {
+    Music,
+    #[default]
+    Effects,
+}
+
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MusicVolume(pub u8);
+impl Default for MusicVolume {
+    fn default() -> Self {
+        Self(MAX_VOLUME)
+    }
+}
+
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SfxVolume(pub u8);
+impl Default for SfxVolume {
+    fn default() -> Self {
+        Self(MAX_VOLUME)
+    }
+}
+
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Muted {
+    pub music: bool,
+    pub effects: bool,
+}
+
+/// The sound that plays for each correctly typed letter.
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeySounds {
+    /// The same flap every time.
+    Plain,
+    /// A flap with a slightly different pitch every time.
+    #[default]
+    Varied,
+    /// A random key from the click pack, falling back to varied flaps until it has loaded.
+    Clicks,
+}
+
+/// Whether a chime plays when a word is completed.
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WordChime(pub bool);
+impl Default for WordChime {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[derive(Resource)]
+struct ClickPack(Vec<Handle<AudioSource>>);
+
+/// Picks the sound for each keystroke.
+#[derive(SystemParam)]
+pub struct KeystrokeSounds<'w> {
+    key_sounds: Res<'w, KeySounds>,
+    click_pack: Option<Res<'w, ClickPack>>,
+    audio_assets: Res<'w, AudioAssets>,
+    audio_sources: Res<'w, Assets<AudioSource>>,
+}
+impl KeystrokeSounds<'_> {
+    pub fn flap(&self) -> impl Bundle {
+        let mut rng = rand::rng();
+
+        let clicks = self
+            .click_pack
+            .as_ref()
+            .filter(|_| *self.key_sounds == KeySounds::Clicks)
+            .and_then(|pack| {
+                pack.0
+                    .iter()
+                    .filter(|handle| self.audio_sources.contains(*handle))
+                    .choose(&mut rng)
+            });
+
+        let (handle, jitter) = match (*self.key_sounds, clicks) {
+            (KeySounds::Plain, _) => (self.audio_assets.flap.clone(), 0.),
+            (_, Some(click)) => (click.clone(), KEYSTROKE_PITCH_JITTER / 2.),
+            (_, None) => (self.audio_assets.flap.clone(), KEYSTROKE_PITCH_JITTER),
+        };
+
+        let speed = 1. + jitter * rng.random_range(-1.0..=1.0);
+
+        (
+            AudioPlayer(handle),
+            PlaybackSettings::DESPAWN.with_speed(speed),
+            AudioCategory::Effects,
+        )
+    }
+}
+
+/// The volume and mute settings of every category.
+#[derive(SystemParam)]
+pub struct Mix<'w> {
+    music_volume: Res<'w, MusicVolume>,
+    sfx_volume: Res<'w, SfxVolume>,
+    muted: Res<'w, Muted>,
+}
+impl Mix<'_> {
+    pub fn volume(&self, category: AudioCategory) -> Volume {
+        let (level, muted) = match category {
+            AudioCategory::Music => (self.music_volume.0, self.muted.music),
+            AudioCategory::Effects => (self.sfx_volume.0, self.muted.effects),
+        };
+
+        if muted {
+            return Volume::SILENT;
+        }
+
+        Volume::Linear(level as f32 / MAX_VOLUME as f32)
+    }
+
+    fn is_changed(&self) -> bool {
+        self.music_volume.is_changed() || self.sfx_volume.is_changed() || self.muted.is_changed()
+    }
+}
+
+/// The music that plays during a run, which speeds up along with the birb.
+#[derive(Component)]
+pub struct GameMusic;
+
+#[derive(Component)]
+struct TapeStop {
+    from: f32,
+    timer: Timer,
+}
+
+#[derive(Component)]
+pub struct FadeIn(Timer);
+#[derive(Component)]
+pub struct FadeOut(Timer);
+
+impl Plugin for AudioMixPlugin {
+    fn build(&self, app: &mut App) {
+        // Audio starts playing in `PostUpdate` after transforms are propagated.
+        app.add_systems(
+            PostUpdate,
+            apply_initial_volume.before(TransformSystems::Propagate),
+        );
+        app.add_systems(Update, (apply_mix, crossfade, tape_stop));
+        app.add_systems(Update, music_intensity.run_if(in_state(AppState::Playing)));
+        app.add_systems(OnEnter(AppState::Crashing), crash_stinger);
+        app.add_systems(
+            Update,
+            (
+                load_click_pack.run_if(resource_changed::<KeySounds>),
+                word_chime.run_if(|chime: Res<WordChime>| chime.0),
+            ),
+        );
+    }
+}
+
+/// A sound effect that plays once.
+pub fn sound_effect(handle: Handle<AudioSource>) -> impl Bundle {
+    (
+        AudioPlayer(handle),
+        PlaybackSettings::DESPAWN,
+        AudioCategory::Effects,
+    )
+}
+
+/// A looping song that fades in.
+pub fn music(handle: Handle<AudioSource>) -> impl Bundle {
+    (
+        AudioPlayer(handle),
+        PlaybackSettings::LOOP,
+        MusicController,
+        AudioCategory::Music,
+        FadeIn(Timer::from_seconds(CROSSFADE_SECS, TimerMode::Once)),
+    )
+}
+
+/// Fades out any music that's playing, despawning it once it's silent.
+pub fn fade_out_music(commands: &mut Commands, query: &Query<Entity, MusicPlaying>) {
+    for entity in query {
+        commands
+            .entity(entity)
+            .remove::<FadeIn>()
+            .insert(FadeOut(Timer::from_seconds(
+                CROSSFADE_SECS,
+                TimerMode::Once,
+            )));
+    }
+}
+
+/// Filters for music that isn't already fading out.
+pub type MusicPlaying = (With<MusicController>, Without<FadeOut>);
+
+/// Sets the volume of sounds as they're spawned, before they start playing.
+fn apply_initial_volume(
+    mut query: Query<
+        (&mut PlaybackSettings, Option<&AudioCategory>, Has<FadeIn>),
+        Added<PlaybackSettings>,
+    >,
+    mix: Mix,
+) {
+    for (mut settings, category, fading_in) in query.iter_mut() {
+        settings.volume = if fading_in {
+            Volume::SILENT
+        } else {
+            mix.volume(category.copied().unwrap_or_default())
+        };
+    }
+}
+
+fn apply_mix(
+    mut query: Query<(&mut AudioSink, Option<&AudioCategory>), (Without<FadeIn>, Without<FadeOut>)>,
+    mix: Mix,
+) {
+    if !mix.is_changed() {
+        return;
+    }
+
+    for (mut sink, category) in query.iter_mut() {
+        sink.set_volume(mix.volume(category.copied().unwrap_or_default()));
+    }
+}
+
+fn crossfade(
+    mut commands: Commands,
+    mut fade_in_query: Query<(Entity, &mut AudioSink, &mut FadeIn)>,
+    mut fade_out_query: Query<(Entity, &mut AudioSink, &mut FadeOut), Without<FadeIn>>,
+    mix: Mix,
+    time: Res<Time<Real>>,
+) {
+    let volume = mix.volume(AudioCategory::Music);
+
+    for (entity, mut sink, mut fade) in fade_in_query.iter_mut() {
+        fade.0.tick(time.delta());
+        sink.set_volume(volume * Volume::Linear(fade.0.fraction()));
+
+        if fade.0.is_finished() {
+            commands.entity(entity).remove::<FadeIn>();
+        }
+    }
+
+    for (entity, mut sink, mut fade) in fade_out_query.iter_mut() {
+        fade.0.tick(time.delta());
+        sink.set_volume(volume * Volume::Linear(fade.0.fraction_remaining()));
+
+        if fade.0.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Plays the game music faster as the birb approaches its top speed.
+fn music_intensity(
+    query: Query<&AudioSink, (With<GameMusic>, Without<TapeStop>)>,
+    speed: Res<Speed>,
+    difficulty: Res<ActiveDifficulty>,
+) {
+    let intensity = speed.build_up(difficulty.preset.start_speed);
+
+    for sink in &query {
+        sink.set_speed(1. + MAX_TEMPO_INCREASE * intensity);
+    }
+}
+
+/// Winds the game music down and plays a slow, falling stinger when the birb crashes.
+fn crash_stinger(
+    mut commands: Commands,
+    query: Query<(Entity, Option<&AudioSink>), (With<GameMusic>, Without<FadeOut>)>,
+    audio_assets: Res<AudioAssets>,
+) {
+    for (entity, sink) in &query {
+        commands.entity(entity).remove::<FadeIn>().insert((
+            FadeOut(Timer::from_seconds(CROSSFADE_SECS, TimerMode::Once)),
+            TapeStop {
+                from: sink.map_or(1., |sink| sink.speed()),
+                timer: Timer::from_seconds(CROSSFADE_SECS, TimerMode::Once),
+            },
+        ));
+    }
+
+    commands.spawn((
+        AudioPlayer(audio_assets.stinger.clone()),
+        PlaybackSettings::DESPAWN,
+        AudioCategory::Music,
+        Name::new("CrashStinger"),
+    ));
+}
+
+fn tape_stop(mut query: Query<(&AudioSink, &mut TapeStop)>, time: Res<Time<Real>>) {
+    for (sink, mut tape_stop) in query.iter_mut() {
+        tape_stop.timer.tick(time.delta());
+
+        let from = tape_stop.from;
+        sink.set_speed(from.lerp(TAPE_STOP_SPEED, tape_stop.timer.fraction()));
+    }
+}
+
+fn load_click_pack(
+    mut commands: Commands,
+    key_sounds: Res<KeySounds>,
+    click_pack: Option<Res<ClickPack>>,
+    asset_server: Res<AssetServer>,
+) {
+    if *key_sounds != KeySounds::Clicks || click_pack.is_some() {
+        return;
+    }
+
+    commands.insert_resource(ClickPack(
+        CLICK_PACK
+            .iter()
+            .map(|path| asset_server.load(*path))
+            .collect(),
+    ));
+}
+
+/// Plays a chime when one of the words in the top or bottom bar is completed.
+fn word_chime(
+    mut commands: Commands,
+    mut events: MessageReader<Action>,
+    query: Query<&TypingTarget>,
+    audio_assets: Res<AudioAssets>,
+) {
+    for e in events.read() {
+        let Action::NewWord(entity) = e else {
+            continue;
+        };
+
+        if query
+            .get(*entity)
+            .is_ok_and(|target| !target.letter_actions.is_empty())
+        {
+            commands.spawn(sound_effect(audio_assets.chime.clone()));
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Flies the birb through thousands of obstacles as quickly as possible, printing how many
+//! entities, meshes and materials exist along the way. Thanks to pooling, the counts should
+//! level off after the first few obstacles rather than creeping up over the course of the run.
+//!
+//! Only built with the `benchmark` feature. Run with `--benchmark [obstacles]`, ideally in
+//! release mode.
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    prelude::*,
+    time::TimeUpdateStrategy,
+    window::{PresentMode, PrimaryWindow},
+};
+
+use crate::{
+    collision, difficulty::ActiveDifficulty, movement, Action, AppState, Birb, ScoreCollider,
+    TargetPosition, Used, BIRB_MAX_Y, BIRB_MIN_Y,
+};
+
+const DEFAULT_OBSTACLES: u32 = 5000;
+/// How often the asset counts are printed, in obstacles.
+const REPORT_EVERY: u32 = 250;
+/// Every frame advances time by this much, so that the run plays out much faster than real
+/// time while still passing through every obstacle.
+const TIMESTEP: f32 = 1. / 20.;
+
+pub enum BenchmarkPlugin {
+    Disabled,
+    Enabled { obstacles: u32 },
+}
+impl BenchmarkPlugin {
+    pub fn from_args() -> Self {
+        let mut args = std::env::args().skip(1).peekable();
+
+        while let Some(arg) = args.next() {
+            if arg != "--benchmark" {
+                continue;
+            }
+
+            let obstacles = args
+                .peek()
+                .and_then(|arg| arg.parse().ok())
+                .unwrap_or(DEFAULT_OBSTACLES);
+
+            return BenchmarkPlugin::Enabled { obstacles };
+        }
+
+        BenchmarkPlugin::Disabled
+    }
+}
+
+#[derive(Resource)]
+struct Benchmark {
+    obstacles: u32,
+    reported: u32,
+    /// The counts at the first report, which later ones are compared to.
+    baseline: Option<(u32, Counts)>,
+    started: Instant,
+}
+
+#[derive(Clone, Copy)]
+struct Counts {
+    entities: usize,
+    meshes: usize,
+    materials: usize,
+}
+
+impl Plugin for BenchmarkPlugin {
+    fn build(&self, app: &mut App) {
+        let BenchmarkPlugin::Enabled { obstacles } = *self else {
+            return;
+        };
+
+        app.insert_resource(Benchmark {
+            obstacles,
+            reported: 0,
+            baseline: None,
+            started: Instant::now(),
+        });
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            TIMESTEP,
+        )));
+
+        app.add_systems(Startup, disable_vsync);
+        app.add_systems(Update, auto_start.run_if(in_state(AppState::StartScreen)));
+        app.add_systems(
+            Update,
+            (autopilot.after(movement).before(collision), report)
+                .run_if(in_state(AppState::Playing)),
+        );
+        // The game pauses when the window loses focus, but the benchmark should keep going.
+        app.add_systems(OnEnter(AppState::Paused), resume);
+        app.add_systems(OnEnter(AppState::Crashing), crashed);
+    }
+}
+
+fn disable_vsync(mut window_query: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in window_query.iter_mut() {
+        window.present_mode = PresentMode::AutoNoVsync;
+    }
+}
+
+fn auto_start(mut events: MessageWriter<Action>) {
+    events.write(Action::Start);
+}
+
+fn resume(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::Playing);
+}
+
+/// Keeps the birb in the middle of the next gap, so that the run never ends.
+fn autopilot(
+    mut birb_query: Query<(&mut Transform, &mut TargetPosition), With<Birb>>,
+    score_collider_query: Query<&GlobalTransform, (With<ScoreCollider>, Without<Used>)>,
+) {
+    let Ok((mut transform, mut target)) = birb_query.single_mut() else {
+        return;
+    };
+
+    let Some(gap) = score_collider_query
+        .iter()
+        .map(|gap| gap.translation())
+        .filter(|gap| gap.x > transform.translation.x - 1.)
+        .min_by(|a, b| a.x.partial_cmp(&b.x).unwrap())
+    else {
+        return;
+    };
+
+    let y = gap.y.clamp(BIRB_MIN_Y, BIRB_MAX_Y);
+    transform.translation.y = y;
+    target.0.y = y;
+}
+
+fn report(
+    mut benchmark: ResMut<Benchmark>,
+    mut exit: MessageWriter<AppExit>,
+    difficulty: Res<ActiveDifficulty>,
+    entities: Query<()>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+) {
+    let obstacles = difficulty.obstacles;
+    if obstacles < benchmark.reported + REPORT_EVERY && obstacles < benchmark.obstacles {
+        return;
+    }
+    benchmark.reported = obstacles;
+
+    let counts = Counts {
+        entities: entities.iter().count(),
+        meshes: meshes.len(),
+        materials: materials.len(),
+    };
+    let (baseline_obstacles, baseline) = *benchmark.baseline.get_or_insert((obstacles, counts));
+
+    println!(
+        "{obstacles:>6} obstacles: {:>5} entities, {:>4} meshes, {:>4} materials ({:.1}s)",
+        counts.entities,
+        counts.meshes,
+        counts.materials,
+        benchmark.started.elapsed().as_secs_f32(),
+    );
+
+    if obstacles < benchmark.obstacles {
+        return;
+    }
+
+    println!(
+        "Since obstacle {baseline_obstacles}: {:+} entities, {:+} meshes, {:+} materials",
+        counts.entities as isize - baseline.entities as isize,
+        counts.meshes as isize - baseline.meshes as isize,
+        counts.materials as isize - baseline.materials as isize,
+    );
+    exit.write(AppExit::Success);
+}
+
+fn crashed(difficulty: Res<ActiveDifficulty>, mut exit: MessageWriter<AppExit>) {
+    println!(
+        "The birb crashed after {} obstacles, so the benchmark couldn't finish",
+        difficulty.obstacles
+    );
+    exit.write(AppExit::error());
+}
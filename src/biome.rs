@@ -0,0 +1,153 @@
+//! The world changes biome every so often over the course of a run, recoloring the ground, sky,
+//! fog and trees. Obstacles take on a style of their own in each biome, see [`crate::obstacle`].
+
+use bevy::prelude::*;
+
+use crate::{
+    daynight::{Sun, TimeOfDay, MIN_SUN_ILLUMINANCE},
+    difficulty::ActiveDifficulty,
+    AppState, InRun,
+};
+
+/// How many obstacles each biome lasts for.
+const BIOME_OBSTACLES: u32 = 15;
+/// How quickly the sky, fog and light follow the current biome.
+const SKY_TRANSITION_RATE: f32 = 0.5;
+
+pub struct BiomePlugin;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Biome {
+    #[default]
+    Meadow,
+    Desert,
+    Snow,
+    NightCity,
+}
+impl Biome {
+    const ALL: [Biome; 4] = [Biome::Meadow, Biome::Desert, Biome::Snow, Biome::NightCity];
+
+    /// The biome once `obstacles` obstacles have been spawned.
+    fn at(obstacles: u32) -> Self {
+        Self::ALL[(obstacles / BIOME_OBSTACLES) as usize % Self::ALL.len()]
+    }
+
+    pub fn palette(&self) -> BiomePalette {
+        match self {
+            Biome::Meadow => BiomePalette {
+                grass: Color::srgb(0.63, 0.96, 0.26),
+                dark_grass: Color::srgb(0.45, 0.8, 0.2),
+                dirt: Color::srgb(0.72, 0.6, 0.38),
+                sky: Color::srgb_u8(177, 214, 222),
+                fog: Color::srgb_u8(177, 214, 222),
+                foliage: Color::srgb(0.2, 0.55, 0.2),
+                sun: 1000.,
+            },
+            Biome::Desert => BiomePalette {
+                grass: Color::srgb(0.93, 0.8, 0.52),
+                dark_grass: Color::srgb(0.85, 0.62, 0.38),
+                dirt: Color::srgb(0.62, 0.45, 0.3),
+                sky: Color::srgb_u8(245, 214, 170),
+                fog: Color::srgb_u8(240, 200, 150),
+                foliage: Color::srgb(0.5, 0.55, 0.25),
+                sun: 1400.,
+            },
+            Biome::Snow => BiomePalette {
+                grass: Color::srgb(0.95, 0.97, 1.),
+                dark_grass: Color::srgb(0.8, 0.86, 0.95),
+                dirt: Color::srgb(0.55, 0.6, 0.68),
+                sky: Color::srgb_u8(200, 215, 230),
+                fog: Color::srgb_u8(225, 232, 240),
+                foliage: Color::srgb(0.15, 0.35, 0.3),
+                sun: 900.,
+            },
+            Biome::NightCity => BiomePalette {
+                grass: Color::srgb(0.2, 0.22, 0.28),
+                dark_grass: Color::srgb(0.12, 0.13, 0.2),
+                dirt: Color::srgb(0.3, 0.28, 0.32),
+                sky: Color::srgb_u8(20, 24, 52),
+                fog: Color::srgb_u8(40, 30, 70),
+                foliage: Color::srgb(0.1, 0.25, 0.3),
+                sun: 250.,
+            },
+        }
+    }
+}
+
+/// The colors and lighting of a biome.
+#[derive(Clone, Copy, Debug)]
+pub struct BiomePalette {
+    pub grass: Color,
+    /// The color of grass on top of hills.
+    pub dark_grass: Color,
+    pub dirt: Color,
+    pub sky: Color,
+    pub fog: Color,
+    /// The color of tree tops.
+    pub foliage: Color,
+    /// The illuminance of the sun.
+    pub sun: f32,
+}
+impl BiomePalette {
+    pub fn mix(&self, other: &Self, t: f32) -> Self {
+        Self {
+            grass: self.grass.mix(&other.grass, t),
+            dark_grass: self.dark_grass.mix(&other.dark_grass, t),
+            dirt: self.dirt.mix(&other.dirt, t),
+            sky: self.sky.mix(&other.sky, t),
+            fog: self.fog.mix(&other.fog, t),
+            foliage: self.foliage.mix(&other.foliage, t),
+            sun: self.sun.lerp(other.sun, t),
+        }
+    }
+}
+
+/// The biome the birb is currently flying towards.
+#[derive(Resource, Default)]
+pub struct CurrentBiome(pub Biome);
+
+impl Plugin for BiomePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentBiome>();
+        app.add_systems(Update, update_biome.run_if(in_state(AppState::Playing)));
+        app.add_systems(Update, transition_sky);
+        app.add_systems(OnExit(InRun), reset_biome);
+    }
+}
+
+fn update_biome(mut current: ResMut<CurrentBiome>, difficulty: Res<ActiveDifficulty>) {
+    let biome = Biome::at(difficulty.obstacles);
+
+    if current.0 != biome {
+        current.0 = biome;
+    }
+}
+
+fn reset_biome(mut current: ResMut<CurrentBiome>) {
+    current.0 = Biome::default();
+}
+
+/// Gradually brings the sky, fog and sun in line with the current biome and time of day.
+fn transition_sky(
+    mut clear_color: ResMut<ClearColor>,
+    mut fog_query: Query<&mut DistanceFog>,
+    mut sun_query: Query<&mut DirectionalLight, With<Sun>>,
+    current: Res<CurrentBiome>,
+    time_of_day: Res<TimeOfDay>,
+    time: Res<Time<Real>>,
+) {
+    let palette = current.0.palette();
+    let sky = time_of_day.sky();
+    let t = 1. - (-SKY_TRANSITION_RATE * time.delta_secs()).exp();
+
+    clear_color.0 = clear_color.0.mix(&sky.tinted(palette.sky), t);
+
+    for mut fog in fog_query.iter_mut() {
+        fog.color = fog.color.mix(&sky.tinted(palette.fog), t);
+    }
+
+    let illuminance = (palette.sun * sky.sun_scale).max(MIN_SUN_ILLUMINANCE);
+    for mut sun in sun_query.iter_mut() {
+        sun.illuminance = sun.illuminance.lerp(illuminance, t);
+    }
+}
@@ -0,0 +1,147 @@
+//! The camera gently follows the birb up and down, pulls back to show more of what's coming as the
+//! birb speeds up and shakes when the birb bumps into the floor, the ceiling or an obstacle.
+//!
+//! All of this can be turned off with the reduced motion setting, which keeps the camera still.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{difficulty::ActiveDifficulty, movement, AppState, Birb, InRun, Speed, BIRB_START_Y};
+
+/// How much of the birb's height the camera follows.
+const FOLLOW_AMOUNT: f32 = 0.3;
+/// How quickly the camera catches up with where it should be.
+const FOLLOW_RATE: f32 = 2.;
+/// How far the camera pulls back at the max speed, and how far ahead it moves to show more of
+/// the upcoming obstacles.
+const PULL_BACK: f32 = 2.5;
+const LOOK_AHEAD: f32 = 1.5;
+
+/// How far the camera moves and rolls when shaking as hard as it can.
+const MAX_SHAKE_OFFSET: f32 = 0.25;
+const MAX_SHAKE_ROLL: f32 = 0.03;
+/// How quickly shaking dies down, in trauma per second.
+const SHAKE_DECAY: f32 = 1.5;
+const SHAKE_FREQUENCY: f32 = 9.;
+
+/// How hard the camera shakes when the birb bumps into the floor or ceiling, when the shield
+/// absorbs a hit and when the birb crashes.
+pub const BUMP_SHAKE: f32 = 0.3;
+pub const SHIELD_SHAKE: f32 = 0.5;
+const CRASH_SHAKE: f32 = 0.8;
+
+pub struct CameraControllerPlugin;
+
+/// Whether the camera should keep still, as set in the settings.
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReducedMotion(pub bool);
+
+/// Shakes the camera. Shakes add up, to a maximum of 1.
+#[derive(Message)]
+pub struct CameraShake(pub f32);
+
+/// Where the camera should be, before any shaking.
+#[derive(Component)]
+pub struct CameraRig {
+    /// Where the camera sits when the birb is at its starting height and speed.
+    home: Transform,
+    pub pose: Transform,
+    trauma: f32,
+}
+impl CameraRig {
+    pub fn new(home: Transform) -> Self {
+        Self {
+            home,
+            pose: home,
+            trauma: 0.,
+        }
+    }
+}
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<CameraShake>();
+        app.add_systems(
+            Update,
+            follow_birb
+                .after(movement)
+                .run_if(in_state(AppState::Playing)),
+        );
+        app.add_systems(OnEnter(AppState::Crashing), crash_shake);
+        // After everything that moves the rig, including the death camera.
+        app.add_systems(PostUpdate, shake_camera.before(TransformSystems::Propagate));
+        // The end screen is seen from the usual spot, rather than pulled back for the speed the
+        // birb crashed at.
+        app.add_systems(OnEnter(AppState::EndScreen), reset_camera);
+        app.add_systems(OnExit(InRun), reset_camera);
+    }
+}
+
+fn follow_birb(
+    mut camera_query: Query<&mut CameraRig>,
+    birb_query: Query<&Transform, With<Birb>>,
+    speed: Res<Speed>,
+    difficulty: Res<ActiveDifficulty>,
+    reduced_motion: Res<ReducedMotion>,
+    time: Res<Time>,
+) {
+    let (Ok(mut rig), Ok(birb)) = (camera_query.single_mut(), birb_query.single()) else {
+        return;
+    };
+
+    let mut target = rig.home.translation;
+
+    if !reduced_motion.0 {
+        target.y += (birb.translation.y - BIRB_START_Y) * FOLLOW_AMOUNT;
+
+        let speed_up = speed.build_up(difficulty.preset.start_speed);
+        target += rig.home.back() * speed_up * PULL_BACK + Vec3::X * speed_up * LOOK_AHEAD;
+    }
+
+    let t = 1. - (-FOLLOW_RATE * time.delta_secs()).exp();
+    rig.pose.translation = rig.pose.translation.lerp(target, t);
+}
+
+fn crash_shake(mut events: MessageWriter<CameraShake>) {
+    events.write(CameraShake(CRASH_SHAKE));
+}
+
+/// Places the camera where its rig says it should be, shaking it around that spot.
+fn shake_camera(
+    mut query: Query<(&mut Transform, &mut CameraRig)>,
+    mut events: MessageReader<CameraShake>,
+    reduced_motion: Res<ReducedMotion>,
+    time: Res<Time<Real>>,
+) {
+    let added: f32 = events.read().map(|shake| shake.0).sum();
+
+    for (mut transform, mut rig) in query.iter_mut() {
+        rig.trauma = (rig.trauma + added - SHAKE_DECAY * time.delta_secs()).clamp(0., 1.);
+
+        *transform = rig.pose;
+
+        if reduced_motion.0 || rig.trauma <= 0. {
+            continue;
+        }
+
+        // Shaking grows quickly with trauma, so that small bumps stay subtle.
+        let shake = rig.trauma * rig.trauma;
+        let phase = time.elapsed_secs() * SHAKE_FREQUENCY * TAU;
+
+        transform.translation += Vec3::new(
+            (phase * 1.1).sin(),
+            (phase * 0.9 + 1.).sin(),
+            (phase * 0.7 + 2.).sin() * 0.5,
+        ) * shake
+            * MAX_SHAKE_OFFSET;
+        transform.rotate_local_z((phase * 1.3 + 3.).sin() * shake * MAX_SHAKE_ROLL);
+    }
+}
+
+fn reset_camera(mut query: Query<&mut CameraRig>) {
+    for mut rig in query.iter_mut() {
+        rig.pose = rig.home;
+        rig.trauma = 0.;
+    }
+}
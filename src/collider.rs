@@ -0,0 +1,131 @@
+//! Precise collision shapes. [`HitBox`](crate::HitBox)es are axis-aligned and only roughly fit
+//! the rotating birbs and round pipes, so they're used to rule out far-away pairs before the
+//! shapes here are tested.
+
+use bevy::{
+    math::{
+        bounding::{Aabb3d, BoundingSphere, BoundingVolume, IntersectsVolume},
+        Vec3A,
+    },
+    prelude::*,
+};
+
+/// Capsules are tested as a row of spheres this many long, which is close enough to a capsule
+/// when the spheres overlap generously.
+const CAPSULE_SPHERES: usize = 4;
+
+/// A capsule lying along the birb's body, tilting along with it.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BirbCollider {
+    pub radius: f32,
+    pub half_length: f32,
+}
+impl BirbCollider {
+    pub const BIRB: Self = Self {
+        radius: 0.22,
+        half_length: 0.08,
+    };
+
+    /// The spheres that make up the capsule for a birb at `translation`, tilted by `rotation_z`.
+    pub fn spheres(&self, translation: Vec3, rotation_z: f32) -> [BoundingSphere; CAPSULE_SPHERES] {
+        let axis = Quat::from_rotation_z(rotation_z) * Vec3::X * self.half_length;
+
+        std::array::from_fn(|i| {
+            let t = i as f32 / (CAPSULE_SPHERES - 1) as f32 * 2. - 1.;
+            BoundingSphere::new(translation + axis * t, self.radius)
+        })
+    }
+
+    /// The capsule for a birb at `translation`, tilted by `rotation_z`.
+    #[cfg(feature = "debug")]
+    pub fn capsule(&self, translation: Vec3, rotation_z: f32) -> (Capsule3d, Isometry3d) {
+        // Capsules are upright, but birbs lie along the x axis.
+        let rotation = Quat::from_rotation_z(rotation_z - std::f32::consts::FRAC_PI_2);
+
+        (
+            Capsule3d::new(self.radius, self.half_length * 2.),
+            Isometry3d::new(translation, rotation),
+        )
+    }
+}
+
+/// An upright cylinder, like a pipe or one of its flanges.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CylinderCollider(pub Cylinder);
+impl CylinderCollider {
+    /// The distance between the surface of the cylinder at `center` and the surface of `sphere`.
+    /// Negative when they overlap.
+    pub fn clearance(&self, center: Vec3, sphere: &BoundingSphere) -> f32 {
+        let offset = Vec3::from(sphere.center()) - center;
+
+        let horizontal = offset.xz().length() - self.0.radius;
+        let vertical = offset.y.abs() - self.0.half_height;
+
+        let distance = if horizontal > 0. && vertical > 0. {
+            Vec2::new(horizontal, vertical).length()
+        } else {
+            horizontal.max(vertical)
+        };
+
+        distance - sphere.radius()
+    }
+
+    /// The smallest clearance between the cylinder and any of `spheres`, skipping the test when
+    /// `aabb` is further away than `margin`.
+    pub fn min_clearance(
+        &self,
+        center: Vec3,
+        aabb: &Aabb3d,
+        spheres: &[BoundingSphere],
+        margin: f32,
+    ) -> Option<f32> {
+        let grown = aabb.grow(Vec3A::splat(margin));
+
+        spheres
+            .iter()
+            .filter(|sphere| grown.intersects(*sphere))
+            .map(|sphere| self.clearance(center, sphere))
+            .min_by(f32::total_cmp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIPE: CylinderCollider = CylinderCollider(Cylinder {
+        radius: 1.,
+        half_height: 2.,
+    });
+
+    fn clearance(sphere_center: Vec3, radius: f32) -> f32 {
+        PIPE.clearance(
+            Vec3::new(5., 1., 0.),
+            &BoundingSphere::new(sphere_center, radius),
+        )
+    }
+
+    #[test]
+    fn clearance_beside_the_wall() {
+        assert!((clearance(Vec3::new(7., 1.5, 0.), 0.5) - 0.5).abs() < 1e-5);
+        assert!((clearance(Vec3::new(5., 1., 3.), 0.5) - 1.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn clearance_above_the_top() {
+        assert!((clearance(Vec3::new(5.5, 4., 0.), 0.25) - 0.75).abs() < 1e-5);
+    }
+
+    #[test]
+    fn clearance_past_the_rim_is_measured_to_the_edge() {
+        // 3 units past the side and 4 above the top, so 5 from the rim.
+        assert!((clearance(Vec3::new(9., 7., 0.), 1.) - 4.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn clearance_is_negative_when_overlapping() {
+        assert!((clearance(Vec3::new(6.2, 1., 0.), 0.5) + 0.3).abs() < 1e-5);
+        // Inside the cylinder, the nearest surface is the closest one.
+        assert!((clearance(Vec3::new(5., 2.5, 0.), 0.25) + 0.75).abs() < 1e-5);
+    }
+}
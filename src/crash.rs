@@ -0,0 +1,190 @@
+//! The short death sequence that plays between the birb crashing and the end screen. Time slows
+//! down, the birb tumbles to the ground in a burst of feathers and debris and the camera closes
+//! in.
+
+use bevy::prelude::*;
+
+use crate::{
+    camera::{CameraRig, ReducedMotion},
+    particles::{burst, ParticleAssets, ParticleKind},
+    AppState, Birb, TargetPosition, GRAVITY, GROUND_Y,
+};
+
+/// How fast time passes while the birb is crashing.
+const SLOW_MOTION_SPEED: f32 = 0.35;
+/// How long the death sequence lasts in real time, before the end screen appears.
+const CRASH_SECS: f32 = 1.8;
+/// How far the camera moves from its usual spot towards the crashed birb.
+const DEATH_CAMERA_ZOOM: f32 = 0.45;
+/// How quickly the camera eases towards the crashed birb.
+const DEATH_CAMERA_EASE: f32 = 3.;
+/// Where the camera ends up relative to the crashed birb at full zoom.
+const DEATH_CAMERA_OFFSET: Vec3 = Vec3::new(1., 1.5, 5.);
+
+const FEATHERS: usize = 14;
+const DEBRIS: usize = 10;
+
+pub struct CrashPlugin;
+
+/// The birb's motion as it tumbles out of the sky.
+#[derive(Component)]
+struct Tumble {
+    velocity: Vec3,
+    spin: f32,
+}
+
+/// Where the camera was when the birb crashed.
+#[derive(Resource)]
+struct CameraHome(Transform);
+
+#[derive(Resource)]
+struct CrashTimer(Timer);
+
+impl Plugin for CrashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::Crashing),
+            (slow_down_time, remember_camera, tumble_birb, shatter),
+        );
+        app.add_systems(OnExit(AppState::Crashing), (restore_time, return_camera));
+        app.add_systems(
+            Update,
+            (tumble, death_camera, finish_crash).run_if(in_state(AppState::Crashing)),
+        );
+    }
+}
+
+fn slow_down_time(mut commands: Commands, mut time: ResMut<Time<Virtual>>) {
+    time.set_relative_speed(SLOW_MOTION_SPEED);
+
+    commands.insert_resource(CrashTimer(Timer::from_seconds(CRASH_SECS, TimerMode::Once)));
+}
+
+fn restore_time(mut time: ResMut<Time<Virtual>>) {
+    time.set_relative_speed(1.);
+}
+
+fn remember_camera(mut commands: Commands, query: Query<&CameraRig>) {
+    if let Ok(rig) = query.single() {
+        commands.insert_resource(CameraHome(rig.pose));
+    }
+}
+
+fn tumble_birb(mut commands: Commands, query: Query<Entity, With<Birb>>) {
+    for entity in &query {
+        // Knocked backwards and up a little by the impact. The birb is no longer flying anywhere,
+        // so it's taken out of the usual movement, which keeps going for everything else.
+        commands
+            .entity(entity)
+            .remove::<TargetPosition>()
+            .insert(Tumble {
+                velocity: Vec3::new(-1.5, 2.5, 0.),
+                spin: 8.,
+            });
+    }
+}
+
+/// Knocks feathers and bits of debris loose from the birb.
+fn shatter(
+    mut commands: Commands,
+    query: Query<&Transform, With<Birb>>,
+    assets: Res<ParticleAssets>,
+) {
+    let Ok(transform) = query.single() else {
+        return;
+    };
+
+    burst(
+        &mut commands,
+        &assets,
+        ParticleKind::Feather,
+        transform.translation,
+        Vec3::Y * 0.25,
+        FEATHERS,
+    );
+    burst(
+        &mut commands,
+        &assets,
+        ParticleKind::Debris,
+        transform.translation,
+        Vec3::Y,
+        DEBRIS,
+    );
+}
+
+fn tumble(mut query: Query<(&mut Transform, &mut Tumble)>, time: Res<Time>) {
+    for (mut transform, mut tumble) in query.iter_mut() {
+        if transform.translation.y <= GROUND_Y {
+            continue;
+        }
+
+        tumble.velocity.y -= GRAVITY * time.delta_secs();
+        transform.translation += tumble.velocity * time.delta_secs();
+        transform.rotate_z(tumble.spin * time.delta_secs());
+
+        if transform.translation.y <= GROUND_Y {
+            transform.translation.y = GROUND_Y;
+        }
+    }
+}
+
+/// Puts the camera back where it was before closing in on the crashed birb, so that the end
+/// screen is seen from the usual spot.
+fn return_camera(
+    mut commands: Commands,
+    mut query: Query<&mut CameraRig>,
+    home: Option<Res<CameraHome>>,
+) {
+    let Some(home) = home else {
+        return;
+    };
+
+    for mut rig in query.iter_mut() {
+        rig.pose = home.0;
+    }
+
+    commands.remove_resource::<CameraHome>();
+}
+
+/// Eases the camera towards the crashed birb.
+fn death_camera(
+    mut camera_query: Query<&mut CameraRig>,
+    birb_query: Query<&Transform, With<Birb>>,
+    home: Res<CameraHome>,
+    reduced_motion: Res<ReducedMotion>,
+    time: Res<Time<Real>>,
+) {
+    if reduced_motion.0 {
+        return;
+    }
+
+    let (Ok(mut rig), Ok(birb)) = (camera_query.single_mut(), birb_query.single()) else {
+        return;
+    };
+
+    let home = home.0;
+
+    let translation = home
+        .translation
+        .lerp(birb.translation + DEATH_CAMERA_OFFSET, DEATH_CAMERA_ZOOM);
+    let rotation = home.rotation.slerp(
+        Transform::from_translation(translation)
+            .looking_at(birb.translation, Vec3::Y)
+            .rotation,
+        DEATH_CAMERA_ZOOM,
+    );
+
+    let t = 1. - (-DEATH_CAMERA_EASE * time.delta_secs()).exp();
+    rig.pose.translation = rig.pose.translation.lerp(translation, t);
+    rig.pose.rotation = rig.pose.rotation.slerp(rotation, t);
+}
+
+fn finish_crash(
+    mut timer: ResMut<CrashTimer>,
+    mut next_state: ResMut<NextState<AppState>>,
+    time: Res<Time<Real>>,
+) {
+    if timer.0.tick(time.delta()).just_finished() {
+        next_state.set(AppState::EndScreen);
+    }
+}
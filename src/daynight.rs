@@ -0,0 +1,158 @@
+//! Days pass over the course of a run. The sun swings across the sky, changing color and
+//! brightness, and the sky shifts through dawn, day, dusk and night.
+//!
+//! The sun never sets or climbs high overhead, so that the shadows of upcoming gaps can always be
+//! spied on the ground.
+
+use std::f32::consts::{FRAC_PI_8, TAU};
+
+use bevy::prelude::*;
+
+use crate::{AppState, InRun};
+
+/// How long a full day lasts, in seconds of play.
+const DAY_SECS: f32 = 150.;
+/// How far the sun swings from side to side over the course of a day.
+const SUN_SWAY: f32 = 0.2;
+/// The sun, or the moon, is never dimmer than this, so that shadows stay readable at night.
+pub const MIN_SUN_ILLUMINANCE: f32 = 300.;
+
+pub struct DayNightPlugin;
+
+/// Marks the light that moves with the time of day.
+#[derive(Component)]
+pub struct Sun;
+
+/// How the sky looks at some time of day.
+#[derive(Clone, Copy, Debug)]
+pub struct Sky {
+    /// Multiplied with the biome's sky and fog colors by [`Sky::tinted`].
+    tint: Color,
+    sun_color: Color,
+    /// Multiplied with the biome's sun illuminance.
+    pub sun_scale: f32,
+    /// How far above the horizon the sun is, in radians.
+    elevation: f32,
+}
+impl Sky {
+    const DAY: Sky = Sky {
+        tint: Color::WHITE,
+        sun_color: Color::WHITE,
+        sun_scale: 1.,
+        elevation: FRAC_PI_8,
+    };
+    const DUSK: Sky = Sky {
+        tint: Color::srgb(1., 0.75, 0.6),
+        sun_color: Color::srgb(1., 0.65, 0.4),
+        sun_scale: 0.7,
+        elevation: FRAC_PI_8 * 0.8,
+    };
+    const NIGHT: Sky = Sky {
+        tint: Color::srgb(0.25, 0.3, 0.5),
+        sun_color: Color::srgb(0.65, 0.75, 1.),
+        sun_scale: 0.4,
+        elevation: FRAC_PI_8,
+    };
+    const DAWN: Sky = Sky {
+        tint: Color::srgb(1., 0.8, 0.8),
+        sun_color: Color::srgb(1., 0.75, 0.6),
+        sun_scale: 0.7,
+        elevation: FRAC_PI_8 * 0.8,
+    };
+
+    /// What the sky looks like over the course of a day, which starts in the morning.
+    const KEYS: [(f32, Sky); 7] = [
+        (0., Sky::DAY),
+        (0.4, Sky::DAY),
+        (0.5, Sky::DUSK),
+        (0.6, Sky::NIGHT),
+        (0.85, Sky::NIGHT),
+        (0.95, Sky::DAWN),
+        (1., Sky::DAY),
+    ];
+
+    fn at(phase: f32) -> Self {
+        let next = Self::KEYS
+            .iter()
+            .position(|(key, _)| *key > phase)
+            .unwrap_or(Self::KEYS.len() - 1);
+        let (from_phase, from) = Self::KEYS[next.saturating_sub(1)];
+        let (to_phase, to) = Self::KEYS[next];
+
+        let t = ((phase - from_phase) / (to_phase - from_phase)).clamp(0., 1.);
+
+        Sky {
+            tint: from.tint.mix(&to.tint, t),
+            sun_color: from.sun_color.mix(&to.sun_color, t),
+            sun_scale: from.sun_scale.lerp(to.sun_scale, t),
+            elevation: from.elevation.lerp(to.elevation, t),
+        }
+    }
+
+    /// Tints `color` with the sky.
+    pub fn tinted(&self, color: Color) -> Color {
+        let color = color.to_linear();
+        let tint = self.tint.to_linear();
+
+        LinearRgba::new(
+            color.red * tint.red,
+            color.green * tint.green,
+            color.blue * tint.blue,
+            color.alpha,
+        )
+        .into()
+    }
+}
+
+/// How far through the day the current run is.
+#[derive(Resource, Default)]
+pub struct TimeOfDay {
+    elapsed: f32,
+}
+impl TimeOfDay {
+    /// How far through the current day it is, from 0 to 1.
+    fn phase(&self) -> f32 {
+        (self.elapsed / DAY_SECS).fract()
+    }
+
+    pub fn sky(&self) -> Sky {
+        Sky::at(self.phase())
+    }
+}
+
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeOfDay>();
+        app.add_systems(Update, pass_time.run_if(in_state(AppState::Playing)));
+        app.add_systems(Update, move_sun);
+        app.add_systems(OnExit(InRun), reset_time_of_day);
+    }
+}
+
+fn pass_time(mut time_of_day: ResMut<TimeOfDay>, time: Res<Time>) {
+    time_of_day.elapsed += time.delta_secs();
+}
+
+fn reset_time_of_day(mut time_of_day: ResMut<TimeOfDay>) {
+    *time_of_day = TimeOfDay::default();
+}
+
+fn move_sun(
+    mut query: Query<(&mut Transform, &mut DirectionalLight), With<Sun>>,
+    time_of_day: Res<TimeOfDay>,
+) {
+    if !time_of_day.is_changed() {
+        return;
+    }
+
+    let sky = time_of_day.sky();
+    let sway = (time_of_day.phase() * TAU).sin() * SUN_SWAY;
+
+    for (mut transform, mut light) in query.iter_mut() {
+        // Shining from behind the camera and off to the side, such that upcoming gaps can be
+        // spied from the shadows.
+        transform.rotation =
+            Quat::from_rotation_x(-sky.elevation) * Quat::from_rotation_y(FRAC_PI_8 + sway);
+        light.color = sky.sun_color;
+    }
+}
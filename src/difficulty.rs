@@ -0,0 +1,359 @@
+//! Difficulty presets shape how a run plays out: how fast the birb starts, how quickly it speeds
+//! up, how far apart obstacles are and how far the gaps jump around.
+//!
+//! The presets are loaded from `assets/presets.difficulty.ron`. If that file can't be loaded, the
+//! error is logged and the game falls back to the presets built into it.
+
+use std::{fmt, io};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, LoadState},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{adaptive::AdaptiveDifficulty, luck::NextGapBag, AppState, ObstacleSpacing, Speed};
+
+pub struct DifficultyPlugin;
+
+/// The preset selected in the settings.
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// A preset that's meant to be edited by players.
+    Custom,
+}
+
+/// How speed builds up from the starting speed to the max speed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Acceleration {
+    Linear,
+    /// Starts gently and speeds up more and more quickly.
+    EaseIn,
+    /// Speeds up quickly at first and levels off.
+    EaseOut,
+}
+impl Acceleration {
+    fn apply(&self, t: f32) -> f32 {
+        match self {
+            Acceleration::Linear => t,
+            Acceleration::EaseIn => t * t,
+            Acceleration::EaseOut => 1. - (1. - t) * (1. - t),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DifficultyPreset {
+    pub start_speed: f32,
+    pub max_speed: f32,
+    pub acceleration: Acceleration,
+    /// How many obstacles it takes to reach the max speed.
+    pub obstacles_to_max_speed: u32,
+    pub start_spacing: f32,
+    pub end_spacing: f32,
+    /// How many obstacles it takes for the spacing to go from `start_spacing` to `end_spacing`.
+    pub obstacles_to_end_spacing: u32,
+    /// Scales how far each gap may be from the previous one.
+    pub gap_variance: f32,
+}
+impl DifficultyPreset {
+    /// The speed once `obstacles` obstacles have been spawned.
+    pub fn speed_at(&self, obstacles: u32) -> f32 {
+        let t = progress(obstacles, self.obstacles_to_max_speed);
+        self.start_speed + (self.max_speed - self.start_speed) * self.acceleration.apply(t)
+    }
+
+    /// The distance to the next obstacle once `obstacles` obstacles have been spawned.
+    pub fn spacing_at(&self, obstacles: u32) -> f32 {
+        let t = progress(obstacles, self.obstacles_to_end_spacing);
+        self.start_spacing.lerp(self.end_spacing, t)
+    }
+}
+
+fn progress(obstacles: u32, total: u32) -> f32 {
+    if total == 0 {
+        return 1.;
+    }
+
+    (obstacles as f32 / total as f32).min(1.)
+}
+
+/// The presets to choose from. Only exists as a resource once the presets have been loaded, or
+/// have failed to load.
+#[derive(Resource, Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct DifficultyPresets {
+    easy: DifficultyPreset,
+    normal: DifficultyPreset,
+    hard: DifficultyPreset,
+    custom: DifficultyPreset,
+}
+impl Default for DifficultyPresets {
+    /// The presets as shipped in `assets/presets.difficulty.ron`.
+    fn default() -> Self {
+        Self {
+            easy: DifficultyPreset {
+                start_speed: 1.6,
+                max_speed: 3.6,
+                acceleration: Acceleration::Linear,
+                obstacles_to_max_speed: 30,
+                start_spacing: 14.,
+                end_spacing: 13.,
+                obstacles_to_end_spacing: 30,
+                gap_variance: 0.8,
+            },
+            normal: DifficultyPreset {
+                start_speed: 2.,
+                max_speed: 4.4,
+                acceleration: Acceleration::Linear,
+                obstacles_to_max_speed: 24,
+                start_spacing: 12.,
+                end_spacing: 12.,
+                obstacles_to_end_spacing: 0,
+                gap_variance: 1.,
+            },
+            hard: DifficultyPreset {
+                start_speed: 2.6,
+                max_speed: 5.2,
+                acceleration: Acceleration::EaseOut,
+                obstacles_to_max_speed: 20,
+                start_spacing: 11.,
+                end_spacing: 9.5,
+                obstacles_to_end_spacing: 30,
+                gap_variance: 1.2,
+            },
+            custom: DifficultyPreset {
+                start_speed: 2.,
+                max_speed: 5.,
+                acceleration: Acceleration::EaseIn,
+                obstacles_to_max_speed: 40,
+                start_spacing: 12.,
+                end_spacing: 10.,
+                obstacles_to_end_spacing: 40,
+                gap_variance: 1.,
+            },
+        }
+    }
+}
+impl DifficultyPresets {
+    pub fn get(&self, difficulty: Difficulty) -> &DifficultyPreset {
+        match difficulty {
+            Difficulty::Easy => &self.easy,
+            Difficulty::Normal => &self.normal,
+            Difficulty::Hard => &self.hard,
+            Difficulty::Custom => &self.custom,
+        }
+    }
+}
+
+/// The presets file, which is loaded on its own rather than with the other assets so that a
+/// mistake in it doesn't keep the game from starting.
+#[derive(Resource)]
+struct DifficultyAssets {
+    presets: Handle<DifficultyPresets>,
+}
+
+impl FromWorld for DifficultyAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            presets: assets.load("presets.difficulty.ron"),
+        }
+    }
+}
+
+/// How strongly the obstacle spacing responds to adaptive difficulty, relative to the speed.
+const ADAPTIVE_SPACING_RESPONSE: f32 = 0.5;
+
+/// The preset the current run is played with, and how far into it the run is.
+#[derive(Resource, Clone, Debug)]
+pub struct ActiveDifficulty {
+    pub preset: DifficultyPreset,
+    pub obstacles: u32,
+    /// Whether the run adapts to the player's typing.
+    pub adaptive: bool,
+    /// How much harder (or easier, when negative) adaptive difficulty has made the run, as a
+    /// fraction of the preset's speed.
+    pub adjustment: f32,
+}
+impl ActiveDifficulty {
+    /// The distance to the next obstacle.
+    pub fn spacing(&self) -> f32 {
+        self.preset.spacing_at(self.obstacles) * (1. - self.adjustment * ADAPTIVE_SPACING_RESPONSE)
+    }
+
+    /// Counts a newly spawned obstacle, returning how much faster the birb should fly.
+    pub fn advance(&mut self) -> f32 {
+        let previous = self.preset.speed_at(self.obstacles);
+        self.obstacles += 1;
+        self.preset.speed_at(self.obstacles) - previous
+    }
+}
+
+#[derive(Default)]
+struct DifficultyPresetsLoader;
+
+#[derive(Debug)]
+pub enum DifficultyPresetsLoaderError {
+    Io(io::Error),
+    Ron(ron::de::SpannedError),
+}
+
+impl fmt::Display for DifficultyPresetsLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DifficultyPresetsLoaderError::Io(err) => write!(f, "{err}"),
+            DifficultyPresetsLoaderError::Ron(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for DifficultyPresetsLoaderError {}
+
+impl AssetLoader for DifficultyPresetsLoader {
+    type Asset = DifficultyPresets;
+    type Settings = ();
+    type Error = DifficultyPresetsLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(DifficultyPresetsLoaderError::Io)?;
+
+        ron::de::from_bytes(&bytes).map_err(DifficultyPresetsLoaderError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["difficulty.ron"]
+    }
+}
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<DifficultyPresets>()
+            .register_asset_loader(DifficultyPresetsLoader);
+
+        app.init_resource::<DifficultyAssets>();
+
+        app.add_systems(
+            Update,
+            load_presets.run_if(
+                in_state(AppState::LoadingAssets).and(not(resource_exists::<DifficultyPresets>)),
+            ),
+        );
+        app.add_systems(OnExit(AppState::LoadingAssets), apply_difficulty);
+        app.add_systems(OnExit(AppState::StartScreen), apply_difficulty);
+    }
+}
+
+/// Inserts the loaded presets, or the built-in ones if they failed to load.
+fn load_presets(
+    mut commands: Commands,
+    assets: Res<DifficultyAssets>,
+    presets: Res<Assets<DifficultyPresets>>,
+    asset_server: Res<AssetServer>,
+) {
+    match asset_server.get_load_state(&assets.presets) {
+        Some(LoadState::Loaded) => {
+            if let Some(presets) = presets.get(&assets.presets) {
+                commands.insert_resource(presets.clone());
+            }
+        }
+        Some(LoadState::Failed(err)) => {
+            error!("Using the built-in difficulty presets: {err}");
+            commands.insert_resource(DifficultyPresets::default());
+        }
+        _ => {}
+    }
+}
+
+pub fn apply_difficulty(
+    mut commands: Commands,
+    mut bag: ResMut<NextGapBag>,
+    difficulty: Res<Difficulty>,
+    adaptive: Res<AdaptiveDifficulty>,
+    presets: Res<DifficultyPresets>,
+) {
+    start_run(
+        &mut commands,
+        &mut bag,
+        presets.get(*difficulty),
+        adaptive.0,
+    );
+}
+
+/// Resets the speed, spacing and gaps for a run played with `preset`.
+pub fn start_run(
+    commands: &mut Commands,
+    bag: &mut NextGapBag,
+    preset: &DifficultyPreset,
+    adaptive: bool,
+) {
+    commands.insert_resource(Speed {
+        current: preset.start_speed,
+        max: preset.max_speed,
+    });
+    commands.insert_resource(ObstacleSpacing(preset.spacing_at(0)));
+    commands.insert_resource(ActiveDifficulty {
+        preset: preset.clone(),
+        obstacles: 0,
+        adaptive,
+        adjustment: 0.,
+    });
+    bag.set_variance(preset.gap_variance);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(acceleration: Acceleration) -> DifficultyPreset {
+        DifficultyPreset {
+            start_speed: 2.,
+            max_speed: 4.,
+            acceleration,
+            obstacles_to_max_speed: 10,
+            ..DifficultyPresets::default().normal
+        }
+    }
+
+    #[test]
+    fn speed_goes_from_start_to_max() {
+        for acceleration in [
+            Acceleration::Linear,
+            Acceleration::EaseIn,
+            Acceleration::EaseOut,
+        ] {
+            let preset = preset(acceleration);
+            assert_eq!(preset.speed_at(0), 2.);
+            assert_eq!(preset.speed_at(10), 4.);
+            assert_eq!(preset.speed_at(25), 4.);
+        }
+    }
+
+    #[test]
+    fn speed_follows_the_acceleration_curve() {
+        assert_eq!(preset(Acceleration::Linear).speed_at(5), 3.);
+        assert_eq!(preset(Acceleration::EaseIn).speed_at(5), 2.5);
+        assert_eq!(preset(Acceleration::EaseOut).speed_at(5), 3.5);
+    }
+
+    #[test]
+    fn speed_starts_at_max_without_obstacles_to_max_speed() {
+        let preset = DifficultyPreset {
+            obstacles_to_max_speed: 0,
+            ..preset(Acceleration::Linear)
+        };
+        assert_eq!(preset.speed_at(0), 4.);
+    }
+}
@@ -0,0 +1,276 @@
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    light::NotShadowCaster,
+    platform::collections::HashMap,
+    prelude::*,
+    scene::SceneInstance,
+};
+
+use crate::{
+    adaptive::AdaptiveDifficulty, difficulty::Difficulty, gap_bag, loading::GltfAssets, luck::Seed,
+    movement, Action, AppState, Birb, CurrentRotationZ, InRun, Score,
+};
+
+const GHOST_ALPHA: f32 = 0.35;
+/// The ghost flies this much closer to the camera than the birb, so that the two never overlap.
+const GHOST_Z: f32 = 2.5;
+
+pub struct GhostPlugin;
+
+/// A single frame of the birb's flight.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub time: f32,
+    pub translation: Vec3,
+    pub rotation_z: f32,
+}
+
+/// What decides where the obstacles of a run are. Besides the seed, the difficulty settings shape
+/// the speed and spacing, so a ghost only races runs played with the same ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Course {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub adaptive: bool,
+}
+
+/// Everything we know about a single run, recorded while playing.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RunRecording {
+    pub course: Course,
+    pub score: u32,
+    pub elapsed: f32,
+    pub samples: Vec<Sample>,
+    pub keystrokes: Vec<(f32, char)>,
+}
+
+impl RunRecording {
+    /// Returns the birb's interpolated translation and rotation at `time`, starting the search
+    /// from `cursor` and advancing it. Returns `None` once the recording has ended.
+    fn sample_at(&self, time: f32, cursor: &mut usize) -> Option<(Vec3, f32)> {
+        while self
+            .samples
+            .get(*cursor + 1)
+            .is_some_and(|next| next.time <= time)
+        {
+            *cursor += 1;
+        }
+
+        let current = self.samples.get(*cursor)?;
+        let Some(next) = self.samples.get(*cursor + 1) else {
+            return (time <= current.time).then_some((current.translation, current.rotation_z));
+        };
+
+        let t = ((time - current.time) / (next.time - current.time)).clamp(0., 1.);
+
+        Some((
+            current.translation.lerp(next.translation, t),
+            current.rotation_z.lerp(next.rotation_z, t),
+        ))
+    }
+}
+
+/// The best run recorded for each course this session.
+#[derive(Resource, Default)]
+pub struct BestRuns(HashMap<Course, RunRecording>);
+
+#[derive(Component)]
+struct Ghost {
+    cursor: usize,
+}
+#[derive(Component)]
+struct Decorated;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BestRuns>()
+            .init_resource::<RunRecording>();
+
+        app.add_systems(
+            OnExit(AppState::StartScreen),
+            (start_recording, spawn_ghost.after(start_recording)),
+        );
+        app.add_systems(
+            Update,
+            (
+                // Keystrokes are stamped with the previous frame's time, which is what replay
+                // playback relies on when feeding them back in.
+                record_keystrokes.before(record_flight),
+                record_flight.after(movement),
+                ghost_movement.after(record_flight),
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+        app.add_systems(OnEnter(AppState::EndScreen), keep_best_run);
+        app.add_systems(Update, race_ghost.run_if(in_state(AppState::EndScreen)));
+        // Must run after SpawnScene schedule.
+        app.add_systems(PostUpdate, decorate_ghost);
+    }
+}
+
+fn start_recording(
+    mut recording: ResMut<RunRecording>,
+    seed: Res<Seed>,
+    difficulty: Res<Difficulty>,
+    adaptive: Res<AdaptiveDifficulty>,
+) {
+    *recording = RunRecording {
+        course: Course {
+            seed: seed.0,
+            difficulty: *difficulty,
+            adaptive: adaptive.0,
+        },
+        ..default()
+    };
+}
+
+fn record_flight(
+    mut recording: ResMut<RunRecording>,
+    query: Query<(&Transform, &CurrentRotationZ), With<Birb>>,
+    time: Res<Time>,
+) {
+    let Ok((transform, rotation)) = query.single() else {
+        return;
+    };
+
+    recording.elapsed += time.delta_secs();
+
+    let sample = Sample {
+        time: recording.elapsed,
+        translation: transform.translation,
+        rotation_z: rotation.0,
+    };
+    recording.samples.push(sample);
+}
+
+fn record_keystrokes(
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    mut recording: ResMut<RunRecording>,
+) {
+    for event in keyboard_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+
+        let Key::Character(ref key_str) = event.logical_key else {
+            continue;
+        };
+
+        let Some(char) = key_str.chars().last() else {
+            continue;
+        };
+
+        let elapsed = recording.elapsed;
+        recording.keystrokes.push((elapsed, char));
+    }
+}
+
+fn keep_best_run(
+    mut best_runs: ResMut<BestRuns>,
+    mut recording: ResMut<RunRecording>,
+    score: Res<Score>,
+) {
+    recording.score = score.0;
+
+    let is_best = best_runs
+        .0
+        .get(&recording.course)
+        .is_none_or(|best| recording.score > best.score);
+
+    if is_best {
+        best_runs.0.insert(recording.course, recording.clone());
+    }
+}
+
+/// Goes back to the course that was just flown, so that the next run races its best ghost. The
+/// ghost only shows up if the difficulty settings are left as they were.
+fn race_ghost(
+    mut commands: Commands,
+    mut events: MessageReader<Action>,
+    mut next_state: ResMut<NextState<AppState>>,
+    recording: Res<RunRecording>,
+) {
+    for e in events.read() {
+        if let Action::Race = e {
+            let seed = Seed(recording.course.seed);
+            commands.insert_resource(seed);
+            commands.insert_resource(gap_bag(&seed));
+            next_state.set(AppState::StartScreen);
+        }
+    }
+}
+
+fn spawn_ghost(
+    mut commands: Commands,
+    gltf_assets: Res<GltfAssets>,
+    best_runs: Res<BestRuns>,
+    recording: Res<RunRecording>,
+) {
+    if !best_runs.0.contains_key(&recording.course) {
+        return;
+    }
+
+    commands.spawn((
+        SceneRoot(gltf_assets.birb.clone()),
+        Transform::from_xyz(0., crate::BIRB_START_Y, GHOST_Z).with_scale(Vec3::splat(0.25)),
+        Ghost { cursor: 0 },
+        Name::new("Ghost"),
+        DespawnOnExit(InRun),
+    ));
+}
+
+fn ghost_movement(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut Ghost)>,
+    best_runs: Res<BestRuns>,
+    recording: Res<RunRecording>,
+) {
+    let Some(best) = best_runs.0.get(&recording.course) else {
+        return;
+    };
+
+    for (entity, mut transform, mut ghost) in query.iter_mut() {
+        let Some((translation, rotation_z)) = best.sample_at(recording.elapsed, &mut ghost.cursor)
+        else {
+            // The best run crashed here, so the ghost has nowhere left to go.
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        transform.translation = translation + Vec3::Z * GHOST_Z;
+        transform.rotation = Quat::from_rotation_z(rotation_z);
+    }
+}
+
+/// Makes the ghost translucent by swapping its scene's materials for see-through copies.
+fn decorate_ghost(
+    mut commands: Commands,
+    spawner: Res<SceneSpawner>,
+    undecorated: Query<(Entity, &SceneInstance), (Without<Decorated>, With<Ghost>)>,
+    material_query: Query<&MeshMaterial3d<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, instance) in undecorated.iter() {
+        if !spawner.instance_is_ready(**instance) {
+            continue;
+        }
+
+        for instance_entity in spawner.iter_instance_entities(**instance) {
+            let Ok(material) = material_query.get(instance_entity) else {
+                continue;
+            };
+            let Some(mut ghostly) = materials.get(&material.0).cloned() else {
+                continue;
+            };
+
+            ghostly.base_color.set_alpha(GHOST_ALPHA);
+            ghostly.alpha_mode = AlphaMode::Blend;
+
+            commands
+                .entity(instance_entity)
+                .insert((MeshMaterial3d(materials.add(ghostly)), NotShadowCaster));
+        }
+
+        commands.entity(entity).insert(Decorated);
+    }
+}
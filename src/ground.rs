@@ -1,27 +1,105 @@
-use std::ops::Range;
-
 use bevy::{
     asset::RenderAssetUsages, mesh::Indices, prelude::*, render::render_resource::PrimitiveTopology,
 };
-use rand::{rng, Rng};
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use rand::prelude::*;
 
-use crate::{AppState, Speed};
+use crate::{
+    biome::{BiomePalette, CurrentBiome},
+    luck::Seed,
+    pool::Pooled,
+    scenery::{scatter, SceneryAssets},
+    AppState, Scrolling, Speed,
+};
 
 const GROUND_LENGTH: f32 = 60.;
 const GROUND_WIDTH: f32 = 40.;
 const GROUND_VERTICES_X: u32 = 30;
 const GROUND_VERTICES_Z: u32 = 20;
 
+/// Hills are kept low near the flight lane so that they never get in the birb's way.
+const LANE_HEIGHT: f32 = 0.15;
+/// The tallest hills, which rise in the background.
+const BACKGROUND_HEIGHT: f32 = 3.;
+/// Hills in the foreground stay fairly low so that they don't hide the birb.
+const FOREGROUND_HEIGHT: f32 = 0.6;
+/// How far from the flight lane the hills start to rise, and where they reach full height.
+const HILLS_START_Z: f32 = 3.;
+const HILLS_FULL_Z: f32 = 16.;
+/// Offsets the seed so that the scenery doesn't follow the same sequence as the gaps.
+const SCENERY_SEED_OFFSET: u64 = 0x7eee;
+
+/// Coherent noise that the ground's shape and colors are sampled from, in terrain coordinates.
 #[derive(Resource)]
-pub struct GroundMesh(Handle<Mesh>);
-impl FromWorld for GroundMesh {
+pub struct Terrain {
+    height: Fbm<Perlin>,
+    patches: Fbm<Perlin>,
+    /// Where the next ground chunk starts along the terrain. Ground chunks scroll by, but the
+    /// terrain itself stays put, so this keeps increasing over the course of a run.
+    next_x: f32,
+    /// The palette at the end of the last ground chunk, which the next one blends from.
+    palette: BiomePalette,
+    /// Places the scenery on each ground chunk. Reseeded along with every run, so that runs that
+    /// share a seed share their scenery too.
+    scenery_rng: StdRng,
+}
+impl FromWorld for Terrain {
     fn from_world(world: &mut World) -> Self {
-        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let seed = *world.resource::<Seed>();
+        // The noise is only seeded once, as changing it would tear the terrain apart.
+        let noise_seed = seed.0 as u32;
 
-        Self(meshes.add(ground_mesh(
-            Vec2::new(GROUND_LENGTH, GROUND_WIDTH),
-            UVec2::new(GROUND_VERTICES_X, GROUND_VERTICES_Z),
-        )))
+        Self {
+            height: Fbm::<Perlin>::new(noise_seed)
+                .set_octaves(4)
+                .set_frequency(0.06),
+            patches: Fbm::<Perlin>::new(noise_seed.wrapping_add(1))
+                .set_octaves(2)
+                .set_frequency(0.15),
+            next_x: 0.,
+            palette: CurrentBiome::default().0.palette(),
+            scenery_rng: scenery_rng(&seed),
+        }
+    }
+}
+impl Terrain {
+    /// The height of the ground chunk that's about to be built, at `x` and `z` relative to the
+    /// chunk's center.
+    pub fn chunk_height(&self, x: f32, z: f32) -> f32 {
+        self.height_at(self.next_x + x, z)
+    }
+
+    fn height_at(&self, x: f32, z: f32) -> f32 {
+        let distance = (z.abs() - HILLS_START_Z) / (HILLS_FULL_Z - HILLS_START_Z);
+        let hilliness = distance.clamp(0., 1.);
+        let max_height = if z < 0. {
+            BACKGROUND_HEIGHT
+        } else {
+            FOREGROUND_HEIGHT
+        };
+
+        let amplitude = LANE_HEIGHT.lerp(max_height, hilliness * hilliness);
+        let noise = self.height.get([x as f64, z as f64]) as f32;
+
+        // Hills rise out of the ground more than valleys dig into it.
+        if noise > 0. {
+            noise * amplitude * 2.
+        } else {
+            noise * LANE_HEIGHT * 2.
+        }
+    }
+
+    fn color_at(&self, x: f32, z: f32, height: f32, palette: &BiomePalette) -> Color {
+        let patch = self.patches.get([x as f64, z as f64]) as f32;
+
+        let grass = palette.grass.mix(
+            &palette.dark_grass,
+            (height / BACKGROUND_HEIGHT).clamp(0., 1.),
+        );
+
+        // Dirt shows through in patches, mostly in the low spots.
+        let dirt = ((patch - 0.2) * 4. - height).clamp(0., 1.);
+        grass.mix(&palette.dirt, dirt)
     }
 }
 
@@ -30,28 +108,45 @@ pub struct GroundMaterial(Handle<StandardMaterial>);
 impl FromWorld for GroundMaterial {
     fn from_world(world: &mut World) -> Self {
         let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
-        Self(materials.add(Color::srgb(0.63, 0.96, 0.26)))
+        // The ground is colored by its vertices.
+        Self(materials.add(Color::WHITE))
     }
 }
 
 #[derive(Component)]
 pub struct Ground;
 
+/// A ground chunk, whether it's part of the world or waiting to be reused.
+#[derive(Component)]
+struct GroundChunk {
+    /// The material shared by the chunk's tree tops.
+    foliage: Handle<StandardMaterial>,
+}
+
 pub struct GroundPlugin;
 
 impl Plugin for GroundPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<GroundMaterial>();
-        app.init_resource::<GroundMesh>();
+        app.init_resource::<Terrain>();
+        app.init_resource::<SceneryAssets>();
         app.add_systems(
             Update,
-            (ground_movement, spawn_ground.after(ground_movement))
-                .run_if(in_state(AppState::Playing)),
+            (ground_movement, spawn_ground.after(ground_movement)).run_if(in_state(Scrolling)),
         );
         app.add_systems(OnEnter(AppState::LoadingPipelines), spawn_ground);
+        app.add_systems(Update, reseed_scenery.run_if(resource_changed::<Seed>));
     }
 }
 
+fn scenery_rng(seed: &Seed) -> StdRng {
+    StdRng::seed_from_u64(seed.0.wrapping_add(SCENERY_SEED_OFFSET))
+}
+
+fn reseed_scenery(mut terrain: ResMut<Terrain>, seed: Res<Seed>) {
+    terrain.scenery_rng = scenery_rng(&seed);
+}
+
 fn ground_movement(
     mut commands: Commands,
     mut query: Query<(Entity, &mut Transform), With<Ground>>,
@@ -63,16 +158,24 @@ fn ground_movement(
     for (entity, mut transform) in query.iter_mut() {
         transform.translation.x -= delta;
         if transform.translation.x < -60. {
-            commands.entity(entity).despawn();
+            commands
+                .entity(entity)
+                .remove::<Ground>()
+                .insert((Pooled, Visibility::Hidden));
         }
     }
 }
 
 fn spawn_ground(
     mut commands: Commands,
-    mesh: Res<GroundMesh>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut terrain: ResMut<Terrain>,
     material: Res<GroundMaterial>,
+    scenery_assets: Res<SceneryAssets>,
+    biome: Res<CurrentBiome>,
     query: Query<&Transform, With<Ground>>,
+    pooled_query: Query<(Entity, &Mesh3d, &GroundChunk), With<Pooled>>,
 ) {
     // keep two ground chunks alive at all times
 
@@ -86,48 +189,111 @@ fn spawn_ground(
         .max_by(|a, b| a.partial_cmp(b).unwrap())
         .unwrap_or(-GROUND_LENGTH);
 
-    commands.spawn((
-        Mesh3d(mesh.0.clone()),
-        MeshMaterial3d(material.0.clone()),
-        Transform::from_xyz(max_x + GROUND_LENGTH, 0.1, 0.),
-        Ground,
-        Name::new("Ground"),
-    ));
+    let palette = biome.0.palette();
+    let mesh = ground_mesh(
+        &terrain,
+        &palette,
+        Vec2::new(GROUND_LENGTH, GROUND_WIDTH),
+        UVec2::new(GROUND_VERTICES_X, GROUND_VERTICES_Z),
+    );
+    let transform = Transform::from_xyz(max_x + GROUND_LENGTH, 0.1, 0.);
+
+    // Reuse a chunk that has scrolled out of view if there is one, rebuilding its mesh in place
+    // and scattering fresh scenery across it.
+    let (chunk, foliage) = match pooled_query.iter().next() {
+        Some((entity, mesh_handle, chunk)) => {
+            if let Some(existing) = meshes.get_mut(&mesh_handle.0) {
+                *existing = mesh;
+            }
+            if let Some(foliage) = materials.get_mut(&chunk.foliage) {
+                foliage.base_color = palette.foliage;
+            }
+
+            commands
+                .entity(entity)
+                .despawn_related::<Children>()
+                .remove::<Pooled>()
+                .insert((transform, Visibility::Inherited, Ground));
+
+            (entity, chunk.foliage.clone())
+        }
+        None => {
+            let foliage = materials.add(palette.foliage);
+            let entity = commands
+                .spawn((
+                    Mesh3d(meshes.add(mesh)),
+                    MeshMaterial3d(material.0.clone()),
+                    transform,
+                    GroundChunk {
+                        foliage: foliage.clone(),
+                    },
+                    Ground,
+                    Name::new("Ground"),
+                ))
+                .id();
+
+            (entity, foliage)
+        }
+    };
+
+    let mut rng = terrain.scenery_rng.clone();
+    commands.entity(chunk).with_children(|parent| {
+        scatter(
+            parent,
+            &terrain,
+            &mut rng,
+            foliage,
+            &scenery_assets,
+            GROUND_LENGTH,
+        );
+    });
+    terrain.scenery_rng = rng;
+
+    terrain.next_x += GROUND_LENGTH;
+    terrain.palette = palette;
 }
 
-pub fn ground_mesh(size: Vec2, num_vertices: UVec2) -> Mesh {
+/// Builds the next chunk of ground along the terrain. Chunks sample the terrain where they
+/// meet, so that they fit together seamlessly, and the colors blend from the previous chunk's
+/// palette to `palette` along the way.
+pub fn ground_mesh(
+    terrain: &Terrain,
+    palette: &BiomePalette,
+    size: Vec2,
+    num_vertices: UVec2,
+) -> Mesh {
     let num_quads = num_vertices - UVec2::splat(1);
     let offset = size / -2.;
 
-    let h_range: Range<f32> = -0.15..0.15;
-
-    let mut rng = rng();
-
     let mut positions: Vec<[f32; 3]> = vec![];
     let mut normals = vec![];
     let mut uvs = vec![];
+    let mut colors = vec![];
     let mut indices = vec![];
 
     for x in 0..num_vertices.x {
+        let blended = terrain.palette.mix(palette, x as f32 / num_quads.x as f32);
+
         for z in 0..num_vertices.y {
-            // Use the same height values for the first and last column so that
-            // ground chunks placed next to each other are seamless.
-            let h = if x == num_vertices.x - 1 {
-                positions[z as usize][1]
-            } else {
-                rng.random_range(h_range.clone())
-            };
-
-            positions.push([
+            let local = Vec2::new(
                 offset.x + x as f32 / num_quads.x as f32 * size.x,
-                h,
                 offset.y + z as f32 / num_quads.y as f32 * size.y,
-            ]);
+            );
+            let terrain_x = terrain.next_x + local.x;
+
+            let h = terrain.height_at(terrain_x, local.y);
+
+            positions.push([local.x, h, local.y]);
             normals.push([0., 1., 0.]);
             uvs.push([0., 0.]);
+            colors.push(
+                terrain
+                    .color_at(terrain_x, local.y, h, &blended)
+                    .to_linear()
+                    .to_f32_array(),
+            );
         }
     }
-
     for x in 0..num_quads.x {
         for z in 0..num_quads.y {
             let i = x * num_vertices.y + z;
@@ -151,6 +317,7 @@ pub fn ground_mesh(size: Vec2, num_vertices: UVec2) -> Mesh {
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
     mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
     mesh.duplicate_vertices();
     mesh.compute_flat_normals();
     mesh
@@ -3,6 +3,7 @@ use bevy_pipelines_ready::{PipelinesReady, PipelinesReadyPlugin};
 
 use crate::{
     asset_tracking::{LoadResource, ResourceHandles},
+    difficulty::DifficultyPresets,
     AppState,
 };
 
@@ -46,8 +47,14 @@ pub struct AudioAssets {
     pub flap: Handle<AudioSource>,
     pub badflap: Handle<AudioSource>,
     pub score: Handle<AudioSource>,
+    pub chime: Handle<AudioSource>,
     pub crash: Handle<AudioSource>,
+    pub stinger: Handle<AudioSource>,
     pub bump: Handle<AudioSource>,
+    pub slow_motion_pickup: Handle<AudioSource>,
+    pub shield_pickup: Handle<AudioSource>,
+    pub magnet_pickup: Handle<AudioSource>,
+    pub double_score_pickup: Handle<AudioSource>,
 }
 
 impl FromWorld for AudioAssets {
@@ -59,8 +66,14 @@ impl FromWorld for AudioAssets {
             flap: assets.load("flap.ogg"),
             badflap: assets.load("badflap.ogg"),
             score: assets.load("score.ogg"),
+            chime: assets.load("chime.ogg"),
             crash: assets.load("crash.ogg"),
+            stinger: assets.load("stinger.ogg"),
             bump: assets.load("bump.ogg"),
+            slow_motion_pickup: assets.load("powerups/slow_motion.ogg"),
+            shield_pickup: assets.load("powerups/shield.ogg"),
+            magnet_pickup: assets.load("powerups/magnet.ogg"),
+            double_score_pickup: assets.load("powerups/double_score.ogg"),
         }
     }
 }
@@ -158,10 +171,11 @@ fn check_pipelines(
 
 fn check_assets(
     resource_handles: Res<ResourceHandles>,
+    presets: Option<Res<DifficultyPresets>>,
     mut next_state: ResMut<NextState<AppState>>,
     mut frames_since_assets_ready: Local<u32>,
 ) {
-    if !resource_handles.is_all_done() {
+    if !resource_handles.is_all_done() || presets.is_none() {
         return;
     }
 
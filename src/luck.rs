@@ -21,17 +21,28 @@ impl NextGapKind {
         }
     }
 }
+/// Seeds the obstacle layout. Runs that share a seed fly through the same gaps.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Seed(pub u64);
+impl Default for Seed {
+    fn default() -> Self {
+        Self(rand::rng().random())
+    }
+}
+
 #[derive(Resource)]
 pub struct NextGapBag {
     rng: StdRng,
     index: usize,
     range: Range<f32>,
+    /// Scales how far each gap may be from the previous one.
+    variance: f32,
     previous_value: f32,
     contents: Vec<NextGapKind>,
 }
 impl NextGapBag {
-    pub fn new(range: Range<f32>, initial_value: f32) -> Self {
-        let mut rng = StdRng::from_os_rng();
+    pub fn new(range: Range<f32>, initial_value: f32, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
 
         let mut contents = vec![
             NextGapKind::VerySmall,
@@ -59,11 +70,16 @@ impl NextGapBag {
         Self {
             rng,
             range,
+            variance: 1.,
             previous_value: initial_value,
             index: 0,
             contents,
         }
     }
+
+    pub fn set_variance(&mut self, variance: f32) {
+        self.variance = variance;
+    }
 }
 impl Iterator for NextGapBag {
     type Item = f32;
@@ -76,7 +92,7 @@ impl Iterator for NextGapBag {
         let kind = self.contents.get(self.index).unwrap();
         let kind_range = kind.to_range();
 
-        let magnitude = self.range.end - self.range.start;
+        let magnitude = (self.range.end - self.range.start) * self.variance;
 
         let scaled_range = (kind_range.start * magnitude)..(kind_range.end * magnitude);
 
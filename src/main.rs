@@ -3,27 +3,69 @@
 
 use bevy::{
     asset::AssetMetaCheck,
-    color::palettes::css::{DEEP_PINK, LIME},
     light::CascadeShadowConfigBuilder,
     math::{
-        bounding::{Aabb3d, Bounded3d, BoundingVolume, IntersectsVolume},
-        Vec3A, Vec3Swizzles,
+        bounding::{Aabb3d, Bounded3d, BoundingSphere, BoundingVolume, IntersectsVolume},
+        Vec3A,
     },
     prelude::*,
 };
 use bevy_simple_prefs::{Prefs, PrefsPlugin};
 
+use adaptive::AdaptiveDifficulty;
+use audio::{
+    fade_out_music, music, sound_effect, AudioCategory, GameMusic, KeySounds, KeystrokeSounds,
+    MusicPlaying, MusicVolume, Muted, SfxVolume, WordChime,
+};
+use biome::CurrentBiome;
+use camera::{CameraRig, CameraShake, ReducedMotion, BUMP_SHAKE, SHIELD_SHAKE};
+use collider::{BirbCollider, CylinderCollider};
+use daynight::Sun;
+use difficulty::{ActiveDifficulty, Difficulty, DifficultyPlugin};
 use loading::{AudioAssets, FontAssets, GltfAssets, LoadingPlugin};
-use luck::NextGapBag;
+use luck::{NextGapBag, Seed};
+use obstacle::{
+    ObstacleAssets, ObstacleParts, ObstacleStyle, FLANGE_HEIGHT, FLANGE_RADIUS, PIPE_RADIUS,
+};
+use pool::Pooled;
+use popup::popup;
+use powerup::ActivePowerUps;
+use rival::{RivalProgress, RivalScore};
+use settings::{FontSize, Fullscreen, TypingMode};
+use weather::WeatherEvents;
 
 use crate::asset_tracking::AssetTrackingPlugin;
 
+mod adaptive;
 mod asset_tracking;
+mod audio;
+#[cfg(all(feature = "benchmark", not(target_arch = "wasm32")))]
+mod benchmark;
+mod biome;
+mod camera;
+mod collider;
+mod crash;
+mod daynight;
+mod difficulty;
+mod ghost;
 mod ground;
 mod loading;
 mod luck;
+mod obstacle;
+mod parallax;
+mod particles;
+mod pause;
+mod pool;
+mod popup;
+mod powerup;
+#[cfg(not(target_arch = "wasm32"))]
+mod replay;
+mod rival;
+mod scenery;
+mod settings;
 mod typing;
 mod ui;
+mod weather;
 mod words;
 
 #[derive(Component)]
@@ -36,15 +78,49 @@ enum AppState {
     LoadingPipelines,
     StartScreen,
     Playing,
-    #[cfg(feature = "debug")]
     Paused,
+    /// The birb has crashed, and is falling to the ground in slow motion.
+    Crashing,
     EndScreen,
 }
 
+/// Exists from the moment a run starts until the player retries or returns to the start screen.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+struct InRun;
+impl ComputedStates for InRun {
+    type SourceStates = AppState;
+
+    fn compute(sources: AppState) -> Option<Self> {
+        matches!(
+            sources,
+            AppState::Playing | AppState::Paused | AppState::Crashing | AppState::EndScreen
+        )
+        .then_some(InRun)
+    }
+}
+
+/// Exists while the world scrolls by, which it keeps doing in slow motion while the birb crashes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+struct Scrolling;
+impl ComputedStates for Scrolling {
+    type SourceStates = AppState;
+
+    fn compute(sources: AppState) -> Option<Self> {
+        matches!(sources, AppState::Playing | AppState::Crashing).then_some(Scrolling)
+    }
+}
+
+/// The screens that can be shown while on the start screen.
+#[derive(SubStates, Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[source(AppState = AppState::StartScreen)]
+enum StartMenu {
+    #[default]
+    Main,
+    Settings,
+}
+
 #[derive(Component)]
 struct Birb;
-#[derive(Component)]
-struct Rival;
 
 #[derive(Component)]
 struct TargetPosition(Vec3);
@@ -62,6 +138,14 @@ pub enum Action {
     IncScore(u32),
     Start,
     Retry,
+    /// Retries on the same course, against the ghost of the best run on it.
+    Race,
+    Resume,
+    Restart,
+    Quit,
+    Settings,
+    ChangeSetting(settings::Setting),
+    Back,
 }
 
 #[derive(Component)]
@@ -72,6 +156,77 @@ struct ScoreCollider;
 struct ObstacleCollider;
 #[derive(Component)]
 struct Used;
+/// Marks obstacles that the birb has come close to without touching. The bonus is only awarded
+/// once the birb is past the obstacle, so that it's never awarded for an obstacle that the birb
+/// goes on to crash into.
+#[derive(Component)]
+struct NearMissed {
+    awarded: bool,
+}
+
+/// How cleanly the birb passed through a gap, judged by its height when crossing the middle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GapPass {
+    Perfect,
+    Clean,
+    Scrape,
+}
+impl GapPass {
+    fn judge(birb_spheres: &[BoundingSphere], gap_y: f32) -> Self {
+        let center = birb_spheres
+            .iter()
+            .map(|sphere| sphere.center().y)
+            .sum::<f32>()
+            / birb_spheres.len() as f32;
+
+        // How close the birb came to the top or bottom of the gap.
+        let clearance = birb_spheres
+            .iter()
+            .map(|sphere| GAP_SIZE / 2. - (sphere.center().y - gap_y).abs() - sphere.radius())
+            .fold(f32::INFINITY, f32::min);
+
+        if (center - gap_y).abs() <= PERFECT_PASS_MARGIN {
+            GapPass::Perfect
+        } else if clearance <= SCRAPE_MARGIN {
+            GapPass::Scrape
+        } else {
+            GapPass::Clean
+        }
+    }
+
+    fn points(&self) -> u32 {
+        match self {
+            GapPass::Perfect => 4,
+            GapPass::Clean => 2,
+            // Scraping usually earns a near miss, which is all it's worth.
+            GapPass::Scrape => 0,
+        }
+    }
+
+    fn label(&self, points: u32) -> String {
+        match self {
+            GapPass::Perfect => format!("+{points} PERFECT"),
+            GapPass::Clean => format!("+{points}"),
+            GapPass::Scrape => "SCRAPE".to_string(),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            GapPass::Perfect => Color::srgb_u8(255, 235, 146),
+            GapPass::Clean => Color::WHITE,
+            GapPass::Scrape => Color::srgb_u8(255, 120, 100),
+        }
+    }
+
+    fn sound_speed(&self) -> f32 {
+        match self {
+            GapPass::Perfect => 1.2,
+            GapPass::Clean => 1.,
+            GapPass::Scrape => 0.8,
+        }
+    }
+}
 
 // Resources
 #[derive(Resource, Default)]
@@ -80,51 +235,62 @@ struct Score(u32);
 struct DistanceToSpawn(f32);
 #[derive(Resource)]
 struct ObstacleSpacing(f32);
-impl Default for ObstacleSpacing {
-    fn default() -> Self {
-        Self(12.)
-    }
-}
 
 #[derive(Resource)]
 struct Speed {
     current: f32,
     max: f32,
 }
-impl Default for Speed {
-    fn default() -> Self {
-        Self {
-            current: 2.,
-            max: 4.4,
-        }
-    }
-}
 impl Speed {
     fn increase(&mut self, amt: f32) {
         self.current = (self.current + amt).min(self.max);
     }
+
+    /// How far the speed has built up from `start` towards the max, from 0 to 1.
+    fn build_up(&self, start: f32) -> f32 {
+        ((self.current - start) / (self.max - start).max(f32::EPSILON)).clamp(0., 1.)
+    }
 }
 
 #[derive(Prefs, Reflect, Default)]
 struct ExamplePrefs {
     high_score: HighScore,
+    rival_progress: RivalProgress,
+    music_volume: MusicVolume,
+    sfx_volume: SfxVolume,
+    muted: Muted,
+    key_sounds: KeySounds,
+    word_chime: WordChime,
+    adaptive_difficulty: AdaptiveDifficulty,
+    difficulty: Difficulty,
+    typing_mode: TypingMode,
+    font_size: FontSize,
+    fullscreen: Fullscreen,
+    weather: WeatherEvents,
+    reduced_motion: ReducedMotion,
 }
 #[derive(Resource, Reflect, Clone, Default)]
 struct HighScore(u32);
 
-#[derive(Component)]
-struct Orbit {
-    angle: f32,
-    distance: f32,
-    origin: Vec2,
-}
-
 const BIRB_START_Y: f32 = 3.;
 
 const BIRB_MIN_Y: f32 = 0.9;
 const BIRB_MAX_Y: f32 = 6.3;
 
+/// Pulls crashed birbs and falling debris down, in units per second squared.
+const GRAVITY: f32 = 9.8;
+/// How high a crashed birb rests above the ground.
+const GROUND_Y: f32 = 0.4;
+
 const GAP_SIZE: f32 = 2.;
+
+/// Passing within this distance of a pipe without touching it counts as a near miss.
+const NEAR_MISS_MARGIN: f32 = 0.15;
+const NEAR_MISS_BONUS: u32 = 1;
+/// Passing through a gap this close to its middle counts as a perfect pass.
+const PERFECT_PASS_MARGIN: f32 = 0.15;
+/// Passing through a gap this close to one of its flanges counts as scraping it.
+const SCRAPE_MARGIN: f32 = 0.1;
 const GAP_START_MIN_Y: f32 = 0.5;
 const GAP_START_MAX_Y: f32 = 6.7 - GAP_SIZE;
 
@@ -152,32 +318,52 @@ fn main() {
     );
 
     app.init_state::<AppState>();
+    app.add_computed_state::<InRun>();
+    app.add_computed_state::<Scrolling>();
+    app.add_sub_state::<StartMenu>();
 
-    app.add_plugins((AssetTrackingPlugin, LoadingPlugin));
+    app.add_plugins((AssetTrackingPlugin, LoadingPlugin, DifficultyPlugin));
 
     #[cfg(feature = "debug")]
     {
         app.add_plugins(bevy::remote::RemotePlugin::default());
         app.add_plugins(bevy::remote::http::RemoteHttpPlugin::default());
-        app.add_systems(Update, pause.run_if(in_state(AppState::Paused)));
-        app.add_systems(Update, pause.run_if(in_state(AppState::Playing)));
     }
 
     app.add_plugins(PrefsPlugin::<ExamplePrefs>::default());
 
+    let seed = Seed::default();
+
     app.init_resource::<Score>()
-        .init_resource::<Speed>()
         .init_resource::<DistanceToSpawn>()
-        .init_resource::<ObstacleSpacing>()
-        .insert_resource(NextGapBag::new(
-            GAP_START_MIN_Y..GAP_START_MAX_Y,
-            BIRB_START_Y,
-        ))
+        .init_resource::<ObstacleAssets>()
+        .insert_resource(seed)
+        .insert_resource(gap_bag(&seed))
         .add_message::<Action>();
 
     app.add_plugins(crate::typing::TypingPlugin)
         .add_plugins(crate::ui::UiPlugin)
-        .add_plugins(crate::ground::GroundPlugin);
+        .add_plugins(crate::ground::GroundPlugin)
+        .add_plugins(crate::biome::BiomePlugin)
+        .add_plugins(crate::daynight::DayNightPlugin)
+        .add_plugins(crate::parallax::ParallaxPlugin)
+        .add_plugins(crate::camera::CameraControllerPlugin)
+        .add_plugins(crate::weather::WeatherPlugin)
+        .add_plugins(crate::ghost::GhostPlugin)
+        .add_plugins(crate::rival::RivalPlugin)
+        .add_plugins(crate::powerup::PowerUpPlugin)
+        .add_plugins(crate::pause::PausePlugin)
+        .add_plugins(crate::crash::CrashPlugin)
+        .add_plugins(crate::particles::ParticlePlugin)
+        .add_plugins(crate::popup::PopupPlugin)
+        .add_plugins(crate::settings::SettingsPlugin)
+        .add_plugins(crate::audio::AudioMixPlugin)
+        .add_plugins(crate::adaptive::AdaptivePlugin);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    app.add_plugins(crate::replay::ReplayPlugin::from_args());
+    #[cfg(all(feature = "benchmark", not(target_arch = "wasm32")))]
+    app.add_plugins(crate::benchmark::BenchmarkPlugin::from_args());
 
     app.add_systems(Startup, setup);
 
@@ -190,14 +376,11 @@ fn main() {
         (start_screen_movement, start_game, bad_flap_sound).run_if(in_state(AppState::StartScreen)),
     );
 
-    app.add_systems(OnExit(AppState::StartScreen), (spawn_rival, game_music));
+    app.add_systems(OnExit(AppState::StartScreen), game_music);
     app.add_systems(
         Update,
         (
-            movement,
-            rival_movement,
             collision,
-            obstacle_movement,
             spawn_obstacle,
             update_target_position,
             update_score,
@@ -205,6 +388,10 @@ fn main() {
         )
             .run_if(in_state(AppState::Playing)),
     );
+    app.add_systems(
+        Update,
+        (movement, obstacle_movement).run_if(in_state(Scrolling)),
+    );
 
     #[cfg(feature = "debug")]
     app.add_systems(Update, debug_hitboxes);
@@ -214,133 +401,27 @@ fn main() {
         (retry_game, bad_flap_sound).run_if(in_state(AppState::EndScreen)),
     );
 
-    app.add_systems(
-        Update,
-        rival_movement_end_screen.run_if(in_state(AppState::EndScreen)),
-    );
-
     app.add_systems(OnEnter(AppState::EndScreen), save_high_score);
 
-    app.add_systems(OnExit(AppState::EndScreen), reset);
+    app.add_systems(Update, bad_flap_sound.run_if(in_state(AppState::Paused)));
 
-    app.run();
-}
+    app.add_systems(OnExit(InRun), reset);
 
-#[cfg(feature = "debug")]
-fn pause(
-    mut keyboard: ResMut<ButtonInput<KeyCode>>,
-    state: Res<State<AppState>>,
-    mut next_state: ResMut<NextState<AppState>>,
-) {
-    if keyboard.just_pressed(KeyCode::Escape) {
-        match state.get() {
-            AppState::Paused => {
-                next_state.set(AppState::Playing);
-            }
-            AppState::Playing => {
-                next_state.set(AppState::Paused);
-            }
-            _ => {}
-        }
-        keyboard.clear();
-    }
+    app.run();
 }
 
 fn reset(mut commands: Commands) {
     commands.insert_resource(Score::default());
-    commands.insert_resource(Speed::default());
+    commands.insert_resource(RivalScore::default());
     commands.insert_resource(DistanceToSpawn::default());
-    commands.insert_resource(ObstacleSpacing::default());
+    // Every run flies a new course, unless the player chooses to race the ghost on the last one.
+    let seed = Seed::default();
+    commands.insert_resource(seed);
+    commands.insert_resource(gap_bag(&seed));
 }
 
-fn rival_movement(mut query: Query<&mut Transform, With<Rival>>, time: Res<Time>) {
-    let speed = 5.;
-
-    for mut transform in query.iter_mut() {
-        if transform.translation.x < 3. {
-            transform.translation.x += speed * time.delta_secs();
-        }
-
-        let floaty = time.elapsed_secs().sin();
-        transform.translation.y = 4. + floaty;
-
-        transform.rotation = Quat::from_rotation_z(time.elapsed_secs().cos() / 4.)
-    }
-}
-
-fn rival_movement_end_screen(
-    mut query: Query<&mut Transform, (With<Rival>, Without<Obstacle>)>,
-    obstacle_query: Query<&Transform, With<Obstacle>>,
-    time: Res<Time>,
-    mut maybe_orbit: Local<Option<Orbit>>,
-) {
-    let Ok(rival) = query.single() else {
-        return;
-    };
-
-    if maybe_orbit.is_none() {
-        let closest_obstacle = obstacle_query
-            .iter()
-            .map(|t| {
-                (
-                    t.translation.xz().distance_squared(rival.translation.xz()),
-                    t.translation.xz(),
-                )
-            })
-            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
-            .unwrap();
-
-        *maybe_orbit = Some(Orbit {
-            angle: closest_obstacle.1.angle_to(rival.translation.xz()),
-            origin: closest_obstacle.1,
-            distance: closest_obstacle.0.sqrt(),
-        })
-    }
-    let orbit = maybe_orbit.as_mut().unwrap();
-
-    // Convert speed to angular speed.
-    let speed = 5. / std::f32::consts::TAU / orbit.distance * 2.;
-    let step = speed * time.delta_secs();
-
-    for mut transform in query.iter_mut() {
-        orbit.angle += step;
-
-        let (sin, cos) = orbit.angle.sin_cos();
-
-        transform.translation.x = sin * orbit.distance + orbit.origin.x;
-        transform.translation.z = cos * orbit.distance + orbit.origin.y;
-
-        // face direction of movement
-        let y_rot = transform.rotation.to_euler(EulerRot::XYZ).1;
-        let diff = orbit.angle - y_rot;
-
-        let next_y_rot = if diff.abs() < step {
-            y_rot + step * diff.signum()
-        } else {
-            orbit.angle
-        };
-
-        transform.rotation = Quat::from_euler(
-            EulerRot::XYZ,
-            0.,
-            next_y_rot,
-            time.elapsed_secs().cos() / 4.,
-        );
-
-        let floaty = time.elapsed_secs().sin();
-        transform.translation.y = 4. + floaty;
-    }
-}
-
-fn spawn_rival(mut commands: Commands, gltf_assets: Res<GltfAssets>) {
-    commands.spawn((
-        SceneRoot(gltf_assets.birb_gold.clone()),
-        Transform::from_xyz(-10., 4., 2.5).with_scale(Vec3::splat(0.25)),
-        CurrentRotationZ(0.),
-        Rival,
-        Name::new("Rival"),
-        DespawnOnExit(AppState::EndScreen),
-    ));
+fn gap_bag(seed: &Seed) -> NextGapBag {
+    NextGapBag::new(GAP_START_MIN_Y..GAP_START_MAX_Y, BIRB_START_Y, seed.0)
 }
 
 fn bad_flap_sound(
@@ -350,10 +431,7 @@ fn bad_flap_sound(
 ) {
     for e in events.read() {
         if let Action::BadFlap = e {
-            commands.spawn((
-                AudioPlayer(audio_assets.badflap.clone()),
-                PlaybackSettings::DESPAWN,
-            ));
+            commands.spawn(sound_effect(audio_assets.badflap.clone()));
         }
     }
 }
@@ -361,17 +439,13 @@ fn bad_flap_sound(
 fn game_music(
     mut commands: Commands,
     audio_assets: Res<AudioAssets>,
-    existing_music_query: Query<(Entity, &AudioSink), With<MusicController>>,
+    existing_music_query: Query<Entity, MusicPlaying>,
 ) {
-    for (entity, sink) in &existing_music_query {
-        sink.pause();
-        commands.entity(entity).despawn();
-    }
+    fade_out_music(&mut commands, &existing_music_query);
 
     commands.spawn((
-        AudioPlayer(audio_assets.game.clone()),
-        PlaybackSettings::LOOP,
-        MusicController,
+        music(audio_assets.game.clone()),
+        GameMusic,
         Name::new("GameMusic"),
     ));
 }
@@ -379,17 +453,12 @@ fn game_music(
 fn start_screen_music(
     mut commands: Commands,
     audio_assets: Res<AudioAssets>,
-    existing_music_query: Query<(Entity, &AudioSink), With<MusicController>>,
+    existing_music_query: Query<Entity, MusicPlaying>,
 ) {
-    for (entity, sink) in &existing_music_query {
-        sink.pause();
-        commands.entity(entity).despawn();
-    }
+    fade_out_music(&mut commands, &existing_music_query);
 
     commands.spawn((
-        AudioPlayer(audio_assets.menu.clone()),
-        PlaybackSettings::LOOP,
-        MusicController,
+        music(audio_assets.menu.clone()),
         Name::new("StartScreenMusic"),
     ));
 }
@@ -405,66 +474,157 @@ fn spawn_birb(mut commands: Commands, gltf_assets: Res<GltfAssets>) {
         TargetPosition(pos),
         CurrentRotationZ(0.),
         hitbox,
+        BirbCollider::BIRB,
         Birb,
         Name::new("Birb"),
-        DespawnOnExit(AppState::EndScreen),
+        DespawnOnExit(InRun),
     ));
 }
 
 fn collision(
     mut commands: Commands,
-    birb_query: Query<(&HitBox, &Transform), With<Birb>>,
+    birb_query: Query<(&HitBox, &BirbCollider, &Transform, &CurrentRotationZ), With<Birb>>,
     score_collider_query: Query<
         (&HitBox, &GlobalTransform, Entity),
         (With<ScoreCollider>, Without<Used>),
     >,
-    obstacle_collider_query: Query<(&HitBox, &GlobalTransform), With<ObstacleCollider>>,
+    obstacle_collider_query: Query<
+        (&HitBox, &CylinderCollider, &GlobalTransform, &ChildOf),
+        With<ObstacleCollider>,
+    >,
+    mut near_missed_query: Query<(&Transform, &mut NearMissed), With<Obstacle>>,
     mut score: ResMut<Score>,
+    mut power_ups: ResMut<ActivePowerUps>,
     mut next_state: ResMut<NextState<AppState>>,
+    mut shake: MessageWriter<CameraShake>,
     audio_assets: Res<AudioAssets>,
+    font_assets: Res<FontAssets>,
 ) {
-    let Ok((birb_hitbox, transform)) = birb_query.single() else {
+    let Ok((birb_hitbox, birb_collider, birb_transform, rotation)) = birb_query.single() else {
         return;
     };
 
-    let birb_aabb = birb_hitbox.0.translated_by(transform.translation);
+    let birb_aabb = birb_hitbox.0.translated_by(birb_transform.translation);
+    let birb_spheres = birb_collider.spheres(birb_transform.translation, rotation.0);
 
     for (score_aabb, transform, entity) in score_collider_query.iter() {
         let score_aabb = score_aabb.0.translated_by(transform.translation());
 
-        if score_aabb.intersects(&birb_aabb) {
-            commands.entity(entity).insert(Used);
-            score.0 += 2;
-
-            commands.spawn((
-                AudioPlayer(audio_assets.score.clone()),
-                PlaybackSettings::DESPAWN,
-            ));
+        if !score_aabb.intersects(&birb_aabb) {
+            continue;
         }
+
+        commands.entity(entity).insert(Used);
+
+        let gap_y = transform.translation().y;
+        let pass = GapPass::judge(&birb_spheres, gap_y);
+        let points = pass.points() * power_ups.score_multiplier();
+        score.0 += points;
+
+        commands.spawn((
+            AudioPlayer(audio_assets.score.clone()),
+            PlaybackSettings::DESPAWN.with_speed(pass.sound_speed()),
+            AudioCategory::Effects,
+        ));
+        commands.spawn(popup(
+            pass.label(points),
+            pass.color(),
+            birb_transform.translation,
+            font_assets.main.clone(),
+        ));
     }
 
-    let mut hit_obstacle = false;
-    for (obstacle_aabb, transform) in obstacle_collider_query.iter() {
-        let obstacle_aabb = obstacle_aabb.0.translated_by(transform.translation());
+    let mut hit_obstacle = None;
+    let mut near_misses = vec![];
+    for (obstacle_aabb, collider, transform, child_of) in obstacle_collider_query.iter() {
+        let center = transform.translation();
+        let obstacle_aabb = obstacle_aabb.0.translated_by(center);
+
+        let Some(clearance) =
+            collider.min_clearance(center, &obstacle_aabb, &birb_spheres, NEAR_MISS_MARGIN)
+        else {
+            continue;
+        };
 
-        if obstacle_aabb.intersects(&birb_aabb) {
-            hit_obstacle = true;
+        if clearance < 0. {
+            hit_obstacle = Some(child_of.parent());
             break;
         }
+
+        if clearance < NEAR_MISS_MARGIN {
+            near_misses.push(child_of.parent());
+        }
     }
 
-    if hit_obstacle {
-        next_state.set(AppState::EndScreen);
+    let Some(obstacle) = hit_obstacle else {
+        for obstacle in near_misses {
+            if !near_missed_query.contains(obstacle) {
+                commands
+                    .entity(obstacle)
+                    .insert(NearMissed { awarded: false });
+            }
+        }
+
+        // Far enough behind the birb that it can't touch the obstacle anymore.
+        let passed_x = birb_transform.translation.x
+            - birb_collider.radius
+            - birb_collider.half_length
+            - FLANGE_RADIUS
+            - NEAR_MISS_MARGIN;
 
-        commands.spawn((
-            AudioPlayer(audio_assets.crash.clone()),
-            PlaybackSettings::DESPAWN,
-        ));
+        for (transform, mut near_missed) in near_missed_query.iter_mut() {
+            if near_missed.awarded || transform.translation.x > passed_x {
+                continue;
+            }
+
+            near_missed.awarded = true;
+            let points = NEAR_MISS_BONUS * power_ups.score_multiplier();
+            score.0 += points;
+
+            commands.spawn((
+                AudioPlayer(audio_assets.score.clone()),
+                PlaybackSettings::DESPAWN.with_speed(1.3),
+                AudioCategory::Effects,
+            ));
+            commands.spawn(popup(
+                format!("+{points} CLOSE"),
+                Color::WHITE,
+                birb_transform.translation + Vec3::Y * 0.5,
+                font_assets.main.clone(),
+            ));
+        }
+
+        return;
+    };
+
+    // Touching an obstacle rules out a near miss, even when a shield absorbs the hit.
+    commands
+        .entity(obstacle)
+        .insert(NearMissed { awarded: true });
+
+    let had_shield = power_ups.shield;
+
+    if power_ups.absorb_hit() {
+        if had_shield {
+            commands.spawn(sound_effect(audio_assets.bump.clone()));
+            shake.write(CameraShake(SHIELD_SHAKE));
+        }
+    } else {
+        next_state.set(AppState::Crashing);
+
+        commands.spawn(sound_effect(audio_assets.crash.clone()));
     }
 }
 
 #[cfg(feature = "debug")]
-fn debug_hitboxes(hitboxes: Query<(&HitBox, &GlobalTransform)>, mut gizmos: Gizmos) {
+fn debug_hitboxes(
+    hitboxes: Query<(&HitBox, &GlobalTransform)>,
+    birb_colliders: Query<(&BirbCollider, &Transform, &CurrentRotationZ)>,
+    cylinder_colliders: Query<(&CylinderCollider, &GlobalTransform)>,
+    mut gizmos: Gizmos,
+) {
+    use bevy::color::palettes::css;
+
     for (hitbox, transform) in &hitboxes {
         let translated = hitbox.0.translated_by(transform.translation());
         gizmos.cuboid(
@@ -473,16 +633,27 @@ fn debug_hitboxes(hitboxes: Query<(&HitBox, &GlobalTransform)>, mut gizmos: Gizm
             Color::WHITE,
         );
     }
+
+    for (collider, transform, rotation) in &birb_colliders {
+        let (capsule, isometry) = collider.capsule(transform.translation, rotation.0);
+        gizmos.primitive_3d(&capsule, isometry, css::DEEP_PINK);
+    }
+
+    for (collider, transform) in &cylinder_colliders {
+        gizmos.primitive_3d(&collider.0, transform.translation(), css::DEEP_PINK);
+    }
 }
 
 fn spawn_obstacle(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    spacing: Res<ObstacleSpacing>,
+    mut spacing: ResMut<ObstacleSpacing>,
     mut distance: ResMut<DistanceToSpawn>,
     mut speed: ResMut<Speed>,
+    mut difficulty: ResMut<ActiveDifficulty>,
     mut bag: ResMut<NextGapBag>,
+    biome: Res<CurrentBiome>,
+    obstacle_assets: Res<ObstacleAssets>,
+    pooled_query: Query<(Entity, &ObstacleParts), With<Pooled>>,
 ) {
     if distance.0 > 0. {
         return;
@@ -490,115 +661,121 @@ fn spawn_obstacle(
 
     distance.0 = spacing.0;
 
-    speed.increase(0.1);
+    speed.increase(difficulty.advance());
+    spacing.0 = difficulty.spacing();
 
     let gap_start = bag.next().unwrap();
-
-    const FLANGE_HEIGHT: f32 = 0.4;
-    const FLANGE_RADIUS: f32 = 0.8;
-    const CYLINDER_RADIUS: f32 = 0.75;
-    const CYLINDER_RESOLUTION: u32 = 16;
-    const CYLINDER_SEGMENTS: u32 = 1;
+    let style = obstacle_assets.style(ObstacleStyle::for_biome(biome.0));
 
     let bottom_height = gap_start;
     let bottom_primitive = Cylinder {
-        radius: CYLINDER_RADIUS,
+        radius: PIPE_RADIUS,
         half_height: bottom_height / 2.,
     };
-    let bottom_cylinder = meshes.add(
-        bottom_primitive
-            .mesh()
-            .resolution(CYLINDER_RESOLUTION)
-            .segments(CYLINDER_SEGMENTS)
-            .build(),
-    );
     let bottom_y = bottom_height / 2.;
 
     let top_height = 10. - gap_start - GAP_SIZE;
     let top_primitive = Cylinder {
-        radius: CYLINDER_RADIUS,
+        radius: PIPE_RADIUS,
         half_height: top_height / 2.,
     };
-    let top_cylinder = meshes.add(
-        top_primitive
-            .mesh()
-            .resolution(CYLINDER_RESOLUTION)
-            .segments(CYLINDER_SEGMENTS)
-            .build(),
-    );
     let top_y = gap_start + GAP_SIZE + top_height / 2.;
 
     let flange_primitive = Cylinder {
         radius: FLANGE_RADIUS,
         half_height: FLANGE_HEIGHT / 2.,
     };
-    let flange = meshes.add(
-        flange_primitive
-            .mesh()
-            .resolution(CYLINDER_RESOLUTION)
-            .segments(CYLINDER_SEGMENTS)
-            .build(),
-    );
     let bottom_flange_y = gap_start - FLANGE_HEIGHT / 2.;
     let top_flange_y = gap_start + GAP_SIZE + FLANGE_HEIGHT / 2.;
 
     let middle_primitive = Cuboid::new(1.0, GAP_SIZE, 1.0);
-    let middle = meshes.add(middle_primitive);
     let middle_y = gap_start + GAP_SIZE / 2.;
 
+    // Reuse an obstacle that has scrolled out of view if there is one. Its parts are rearranged
+    // below, just like the parts of a brand new obstacle.
+    let (obstacle, parts) = match pooled_query.iter().next() {
+        Some((obstacle, parts)) => {
+            commands
+                .entity(obstacle)
+                .remove::<(Pooled, NearMissed)>()
+                .insert(Visibility::Inherited);
+            commands.entity(parts.gap).remove::<Used>();
+
+            (obstacle, *parts)
+        }
+        None => {
+            let obstacle = commands
+                .spawn((
+                    Visibility::default(),
+                    Name::new("Obstacle"),
+                    DespawnOnExit(InRun),
+                ))
+                .id();
+            let mut part = || commands.spawn(ChildOf(obstacle)).id();
+            let parts = ObstacleParts {
+                bottom: part(),
+                bottom_flange: part(),
+                top: part(),
+                top_flange: part(),
+                gap: part(),
+            };
+            commands.entity(obstacle).insert(parts);
+
+            (obstacle, parts)
+        }
+    };
+
     commands
-        .spawn((
-            Transform::from_xyz(38., 0., 0.),
-            Visibility::default(),
-            Obstacle,
-            Name::new("Obstacle"),
-            DespawnOnExit(AppState::EndScreen),
-        ))
-        .with_children(|parent| {
-            parent.spawn((
-                Mesh3d(bottom_cylinder),
-                MeshMaterial3d(materials.add(Color::from(LIME))),
-                Transform::from_xyz(0., bottom_y, 0.),
-                HitBox(bottom_primitive.aabb_3d(Isometry3d::IDENTITY)),
-                ObstacleCollider,
-            ));
-            parent.spawn((
-                Mesh3d(flange.clone()),
-                MeshMaterial3d(materials.add(Color::from(LIME))),
-                Transform::from_xyz(0., bottom_flange_y, 0.),
-                HitBox(flange_primitive.aabb_3d(Isometry3d::IDENTITY)),
-                ObstacleCollider,
-            ));
+        .entity(obstacle)
+        .insert((Transform::from_xyz(38., 0., 0.), Obstacle));
+
+    commands.entity(parts.bottom).insert((
+        Mesh3d(style.pipe.clone()),
+        MeshMaterial3d(style.pipe_material.clone()),
+        Transform::from_xyz(0., bottom_y, 0.).with_scale(Vec3::new(1., bottom_height, 1.)),
+        HitBox(bottom_primitive.aabb_3d(Isometry3d::IDENTITY)),
+        CylinderCollider(bottom_primitive),
+        ObstacleCollider,
+    ));
+    commands.entity(parts.bottom_flange).insert((
+        Mesh3d(style.flange.clone()),
+        MeshMaterial3d(style.flange_material.clone()),
+        Transform::from_xyz(0., bottom_flange_y, 0.),
+        HitBox(flange_primitive.aabb_3d(Isometry3d::IDENTITY)),
+        CylinderCollider(flange_primitive),
+        ObstacleCollider,
+    ));
 
-            parent.spawn((
-                Mesh3d(top_cylinder),
-                MeshMaterial3d(materials.add(Color::from(LIME))),
-                Transform::from_xyz(0., top_y, 0.),
-                HitBox(top_primitive.aabb_3d(Isometry3d::IDENTITY)),
-                ObstacleCollider,
-            ));
-            parent.spawn((
-                Mesh3d(flange.clone()),
-                MeshMaterial3d(materials.add(Color::from(LIME))),
-                Transform::from_xyz(0., top_flange_y, 0.),
-                HitBox(flange_primitive.aabb_3d(Isometry3d::IDENTITY)),
-                ObstacleCollider,
-            ));
-            parent.spawn((
-                Mesh3d(middle.clone()),
-                MeshMaterial3d(materials.add(Color::from(DEEP_PINK.with_alpha(0.5)))),
-                Transform::from_xyz(0., middle_y, 0.),
-                HitBox(middle_primitive.aabb_3d(Isometry3d::IDENTITY)),
-                Visibility::Hidden,
-                ScoreCollider,
-                Name::new("ScoreCollider"),
-            ));
-        });
+    commands.entity(parts.top).insert((
+        Mesh3d(style.pipe.clone()),
+        MeshMaterial3d(style.pipe_material.clone()),
+        Transform::from_xyz(0., top_y, 0.).with_scale(Vec3::new(1., top_height, 1.)),
+        HitBox(top_primitive.aabb_3d(Isometry3d::IDENTITY)),
+        CylinderCollider(top_primitive),
+        ObstacleCollider,
+    ));
+    commands.entity(parts.top_flange).insert((
+        Mesh3d(style.flange.clone()),
+        MeshMaterial3d(style.flange_material.clone()),
+        Transform::from_xyz(0., top_flange_y, 0.),
+        HitBox(flange_primitive.aabb_3d(Isometry3d::IDENTITY)),
+        CylinderCollider(flange_primitive),
+        ObstacleCollider,
+    ));
+    commands.entity(parts.gap).insert((
+        Mesh3d(obstacle_assets.gap.clone()),
+        MeshMaterial3d(obstacle_assets.gap_material.clone()),
+        Transform::from_xyz(0., middle_y, 0.),
+        HitBox(middle_primitive.aabb_3d(Isometry3d::IDENTITY)),
+        Visibility::Hidden,
+        ScoreCollider,
+        Name::new("ScoreCollider"),
+    ));
 }
 
 fn obstacle_movement(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Transform), With<Obstacle>>,
+    mut query: Query<(Entity, &mut Transform, &ObstacleParts), With<Obstacle>>,
     time: Res<Time>,
     mut distance: ResMut<DistanceToSpawn>,
     speed: Res<Speed>,
@@ -607,10 +784,19 @@ fn obstacle_movement(
 
     distance.0 -= delta;
 
-    for (entity, mut transform) in query.iter_mut() {
+    for (entity, mut transform, parts) in query.iter_mut() {
         transform.translation.x -= delta;
         if transform.translation.x < -30. {
-            commands.entity(entity).despawn();
+            // Set the obstacle aside to be reused, making sure that nothing collides with it in
+            // the meantime.
+            commands
+                .entity(entity)
+                .remove::<Obstacle>()
+                .insert((Pooled, Visibility::Hidden));
+            for part in parts.colliders() {
+                commands.entity(part).remove::<ObstacleCollider>();
+            }
+            commands.entity(parts.gap).remove::<ScoreCollider>();
         }
     }
 }
@@ -695,10 +881,14 @@ fn start_game(mut events: MessageReader<Action>, mut next_state: ResMut<NextStat
     }
 }
 
-fn update_score(mut events: MessageReader<Action>, mut score: ResMut<Score>) {
+fn update_score(
+    mut events: MessageReader<Action>,
+    mut score: ResMut<Score>,
+    power_ups: Res<ActivePowerUps>,
+) {
     for e in events.read() {
         if let Action::IncScore(inc) = e {
-            score.0 += inc
+            score.0 += inc * power_ups.score_multiplier()
         }
     }
 }
@@ -706,8 +896,10 @@ fn update_score(mut events: MessageReader<Action>, mut score: ResMut<Score>) {
 fn update_target_position(
     mut commands: Commands,
     mut events: MessageReader<Action>,
-    mut query: Query<&mut TargetPosition>,
+    mut shake: MessageWriter<CameraShake>,
+    mut query: Query<&mut TargetPosition, With<Birb>>,
     audio_assets: Res<AudioAssets>,
+    keystroke_sounds: KeystrokeSounds,
 ) {
     for e in events.read() {
         match e {
@@ -717,15 +909,10 @@ fn update_target_position(
                     if target.0.y > BIRB_MAX_Y {
                         target.0.y = BIRB_MAX_Y;
 
-                        commands.spawn((
-                            AudioPlayer(audio_assets.bump.clone()),
-                            PlaybackSettings::DESPAWN,
-                        ));
+                        commands.spawn(sound_effect(audio_assets.bump.clone()));
+                        shake.write(CameraShake(BUMP_SHAKE));
                     } else {
-                        commands.spawn((
-                            AudioPlayer(audio_assets.flap.clone()),
-                            PlaybackSettings::DESPAWN,
-                        ));
+                        commands.spawn(keystroke_sounds.flap());
                     }
                 }
             }
@@ -735,15 +922,10 @@ fn update_target_position(
                     if target.0.y < BIRB_MIN_Y {
                         target.0.y = BIRB_MIN_Y;
 
-                        commands.spawn((
-                            AudioPlayer(audio_assets.bump.clone()),
-                            PlaybackSettings::DESPAWN,
-                        ));
+                        commands.spawn(sound_effect(audio_assets.bump.clone()));
+                        shake.write(CameraShake(BUMP_SHAKE));
                     } else {
-                        commands.spawn((
-                            AudioPlayer(audio_assets.flap.clone()),
-                            PlaybackSettings::DESPAWN,
-                        ));
+                        commands.spawn(keystroke_sounds.flap());
                     }
                 }
             }
@@ -754,9 +936,21 @@ fn update_target_position(
 
 fn setup(mut commands: Commands) {
     // camera
+    let camera_home =
+        Transform::from_xyz(4.5, 5.8, 11.7).with_rotation(Quat::from_rotation_x(-0.211));
     commands.spawn((
         Camera3d::default(),
-        Transform::from_xyz(4.5, 5.8, 11.7).with_rotation(Quat::from_rotation_x(-0.211)),
+        camera_home,
+        CameraRig::new(camera_home),
+        // Hides the far edges of the ground, and takes on the color of the current biome.
+        DistanceFog {
+            color: Color::srgb_u8(177, 214, 222),
+            falloff: FogFalloff::Linear {
+                start: 40.,
+                end: 90.,
+            },
+            ..default()
+        },
     ));
 
     // directional 'sun' light
@@ -766,6 +960,7 @@ fn setup(mut commands: Commands) {
             illuminance: 1000.,
             ..default()
         },
+        Sun,
         CascadeShadowConfigBuilder {
             maximum_distance: 40.,
             ..default()
@@ -783,3 +978,44 @@ fn setup(mut commands: Commands) {
 fn save_high_score(score: Res<Score>, mut high_score: ResMut<HighScore>) {
     high_score.0 = score.0;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spheres(y: f32) -> [BoundingSphere; 2] {
+        [
+            BoundingSphere::new(Vec3::new(-0.2, y, 0.), 0.3),
+            BoundingSphere::new(Vec3::new(0.2, y, 0.), 0.3),
+        ]
+    }
+
+    #[test]
+    fn judges_a_centered_pass_perfect() {
+        assert_eq!(GapPass::judge(&spheres(3.), 3.), GapPass::Perfect);
+        assert_eq!(GapPass::judge(&spheres(3.1), 3.), GapPass::Perfect);
+    }
+
+    #[test]
+    fn judges_an_off_center_pass_clean() {
+        assert_eq!(GapPass::judge(&spheres(3.3), 3.), GapPass::Clean);
+        assert_eq!(GapPass::judge(&spheres(2.7), 3.), GapPass::Clean);
+    }
+
+    #[test]
+    fn judges_a_pass_near_the_edge_a_scrape() {
+        // Leaves less than `SCRAPE_MARGIN` between the birb and the top of the gap.
+        let y = 3. + GAP_SIZE / 2. - 0.3 - SCRAPE_MARGIN / 2.;
+        assert_eq!(GapPass::judge(&spheres(y), 3.), GapPass::Scrape);
+        assert_eq!(GapPass::judge(&spheres(6. - y), 3.), GapPass::Scrape);
+    }
+
+    #[test]
+    fn judges_by_the_sphere_closest_to_the_edge() {
+        let birb = [
+            BoundingSphere::new(Vec3::new(-0.2, 3.55, 0.), 0.4),
+            BoundingSphere::new(Vec3::new(0.2, 3.3, 0.), 0.3),
+        ];
+        assert_eq!(GapPass::judge(&birb, 3.), GapPass::Scrape);
+    }
+}
@@ -0,0 +1,168 @@
+//! Obstacles take on a style that suits the biome they're spawned in: green pipes in the meadow,
+//! sandstone columns in the desert, tree trunks in the snow and neon pillars in the city.
+//!
+//! Every style has the same shape as far as collisions go, so that gaps are just as hard to fly
+//! through no matter how they look. Every obstacle of a style shares the same meshes and
+//! materials, and the parts that vary in height are stretched from a mesh of unit height.
+
+use bevy::{color::palettes::css::DEEP_PINK, prelude::*};
+
+use crate::{biome::Biome, GAP_SIZE};
+
+pub const PIPE_RADIUS: f32 = 0.75;
+pub const FLANGE_RADIUS: f32 = 0.8;
+pub const FLANGE_HEIGHT: f32 = 0.4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObstacleStyle {
+    Pipe,
+    StoneColumn,
+    TreeTrunk,
+    NeonPillar,
+}
+impl ObstacleStyle {
+    const ALL: [ObstacleStyle; 4] = [
+        ObstacleStyle::Pipe,
+        ObstacleStyle::StoneColumn,
+        ObstacleStyle::TreeTrunk,
+        ObstacleStyle::NeonPillar,
+    ];
+
+    pub fn for_biome(biome: Biome) -> Self {
+        match biome {
+            Biome::Meadow => ObstacleStyle::Pipe,
+            Biome::Desert => ObstacleStyle::StoneColumn,
+            Biome::Snow => ObstacleStyle::TreeTrunk,
+            Biome::NightCity => ObstacleStyle::NeonPillar,
+        }
+    }
+
+    /// How many sides the style's pipes and flanges have.
+    fn resolution(&self) -> u32 {
+        match self {
+            ObstacleStyle::Pipe => 16,
+            ObstacleStyle::StoneColumn => 8,
+            ObstacleStyle::TreeTrunk => 7,
+            ObstacleStyle::NeonPillar => 6,
+        }
+    }
+
+    fn pipe_material(&self) -> StandardMaterial {
+        match self {
+            ObstacleStyle::Pipe => Color::srgb(0., 1., 0.).into(),
+            ObstacleStyle::StoneColumn => StandardMaterial {
+                base_color: Color::srgb(0.85, 0.72, 0.5),
+                perceptual_roughness: 1.,
+                ..default()
+            },
+            ObstacleStyle::TreeTrunk => StandardMaterial {
+                base_color: Color::srgb(0.42, 0.28, 0.16),
+                perceptual_roughness: 1.,
+                ..default()
+            },
+            ObstacleStyle::NeonPillar => StandardMaterial {
+                base_color: Color::srgb(0.08, 0.06, 0.12),
+                metallic: 0.6,
+                perceptual_roughness: 0.3,
+                ..default()
+            },
+        }
+    }
+
+    fn flange_material(&self) -> StandardMaterial {
+        match self {
+            ObstacleStyle::Pipe => Color::srgb(0., 1., 0.).into(),
+            ObstacleStyle::StoneColumn => StandardMaterial {
+                base_color: Color::srgb(0.7, 0.58, 0.4),
+                perceptual_roughness: 1.,
+                ..default()
+            },
+            // Snow piled up on the cut ends of the trunks.
+            ObstacleStyle::TreeTrunk => Color::srgb(0.95, 0.97, 1.).into(),
+            ObstacleStyle::NeonPillar => StandardMaterial {
+                base_color: Color::srgb(1., 0.2, 0.8),
+                emissive: LinearRgba::rgb(4., 0.8, 3.2),
+                ..default()
+            },
+        }
+    }
+}
+
+/// The parts of an obstacle, which are rearranged whenever the obstacle is reused.
+#[derive(Component, Clone, Copy)]
+pub struct ObstacleParts {
+    pub bottom: Entity,
+    pub bottom_flange: Entity,
+    pub top: Entity,
+    pub top_flange: Entity,
+    /// The invisible volume between the pipes that scores a point.
+    pub gap: Entity,
+}
+impl ObstacleParts {
+    /// The parts that the birb can crash into.
+    pub fn colliders(&self) -> [Entity; 4] {
+        [self.bottom, self.bottom_flange, self.top, self.top_flange]
+    }
+}
+
+/// The meshes and materials shared by every obstacle of a style.
+pub struct StyleAssets {
+    /// A pipe of unit height, to be stretched to the height of each obstacle.
+    pub pipe: Handle<Mesh>,
+    pub flange: Handle<Mesh>,
+    pub pipe_material: Handle<StandardMaterial>,
+    pub flange_material: Handle<StandardMaterial>,
+}
+
+#[derive(Resource)]
+pub struct ObstacleAssets {
+    styles: Vec<StyleAssets>,
+    pub gap: Handle<Mesh>,
+    pub gap_material: Handle<StandardMaterial>,
+}
+impl ObstacleAssets {
+    pub fn style(&self, style: ObstacleStyle) -> &StyleAssets {
+        &self.styles[style as usize]
+    }
+}
+impl FromWorld for ObstacleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let gap = meshes.add(Cuboid::new(1.0, GAP_SIZE, 1.0));
+        let style_meshes: Vec<(Handle<Mesh>, Handle<Mesh>)> = ObstacleStyle::ALL
+            .iter()
+            .map(|style| {
+                let pipe = meshes.add(
+                    Cylinder::new(PIPE_RADIUS, 1.)
+                        .mesh()
+                        .resolution(style.resolution()),
+                );
+                let flange = meshes.add(
+                    Cylinder::new(FLANGE_RADIUS, FLANGE_HEIGHT)
+                        .mesh()
+                        .resolution(style.resolution()),
+                );
+                (pipe, flange)
+            })
+            .collect();
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let gap_material = materials.add(Color::from(DEEP_PINK.with_alpha(0.5)));
+        let styles = ObstacleStyle::ALL
+            .iter()
+            .zip(style_meshes)
+            .map(|(style, (pipe, flange))| StyleAssets {
+                pipe,
+                flange,
+                pipe_material: materials.add(style.pipe_material()),
+                flange_material: materials.add(style.flange_material()),
+            })
+            .collect();
+
+        Self {
+            styles,
+            gap,
+            gap_material,
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Distant mountains, hills and clouds that scroll by more slowly than the ground the further
+//! away they are.
+//!
+//! Each layer is made up of a few identical tiles, side by side, that wrap around once they've
+//! scrolled out of view.
+
+use std::f32::consts::TAU;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    color::color_difference::EuclideanDistance,
+    light::{NotShadowCaster, NotShadowReceiver},
+    mesh::Indices,
+    prelude::*,
+    render::render_resource::PrimitiveTopology,
+};
+use rand::prelude::*;
+
+use crate::{biome::CurrentBiome, daynight::TimeOfDay, Scrolling, Speed};
+
+/// How many tiles make up each layer.
+const TILES: usize = 3;
+/// How many points make up the outline of each mountain or hill tile.
+const OUTLINE_POINTS: u32 = 64;
+const CLOUDS_PER_TILE: usize = 6;
+/// How quickly the layers take on the colors of a new biome or time of day.
+const TINT_RATE: f32 = 0.5;
+/// Layers this close to their tint take it on outright, so that they stop changing.
+const TINT_SNAP_DISTANCE: f32 = 0.002;
+
+pub struct ParallaxPlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LayerKind {
+    Mountains,
+    Hills,
+    Clouds,
+}
+
+/// A tile of a background layer.
+#[derive(Component)]
+struct ParallaxTile {
+    /// How fast the layer scrolls, relative to the ground.
+    factor: f32,
+    width: f32,
+}
+
+/// The material shared by every tile of a layer, which follows the colors of the current biome.
+#[derive(Component)]
+struct LayerMaterial {
+    kind: LayerKind,
+    material: Handle<StandardMaterial>,
+}
+
+struct Layer {
+    kind: LayerKind,
+    factor: f32,
+    width: f32,
+    z: f32,
+    /// How high the outline rises on average, and how far it strays from that.
+    height: f32,
+    roughness: f32,
+    /// The outline extends this far down, so that there's no gap beneath it when seen over the
+    /// edge of the ground.
+    bottom: f32,
+}
+
+const LAYERS: [Layer; 3] = [
+    Layer {
+        kind: LayerKind::Clouds,
+        factor: 0.05,
+        width: 200.,
+        z: -80.,
+        height: 16.,
+        roughness: 3.,
+        bottom: 0.,
+    },
+    Layer {
+        kind: LayerKind::Mountains,
+        factor: 0.1,
+        width: 160.,
+        z: -75.,
+        height: 8.,
+        roughness: 6.,
+        bottom: -14.,
+    },
+    Layer {
+        kind: LayerKind::Hills,
+        factor: 0.3,
+        width: 100.,
+        z: -40.,
+        height: 2.,
+        roughness: 2.,
+        bottom: -6.,
+    },
+];
+
+impl LayerKind {
+    fn color(&self, biome: &CurrentBiome, time_of_day: &TimeOfDay) -> Color {
+        let palette = biome.0.palette();
+
+        let color = match self {
+            LayerKind::Mountains => palette.dirt.mix(&palette.sky, 0.4),
+            LayerKind::Hills => palette
+                .dark_grass
+                .mix(&palette.foliage, 0.5)
+                .mix(&palette.sky, 0.2),
+            LayerKind::Clouds => Color::WHITE.mix(&palette.sky, 0.3),
+        };
+
+        // The layers are unlit, so they need to be darkened for the night by hand.
+        time_of_day.sky().tinted(color)
+    }
+}
+
+impl Plugin for ParallaxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_layers);
+        app.add_systems(Update, scroll_layers.run_if(in_state(Scrolling)));
+        app.add_systems(Update, tint_layers);
+    }
+}
+
+fn spawn_layers(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    biome: Res<CurrentBiome>,
+    time_of_day: Res<TimeOfDay>,
+) {
+    let mut rng = rand::rng();
+
+    for layer in &LAYERS {
+        let material = materials.add(StandardMaterial {
+            base_color: layer.kind.color(&biome, &time_of_day),
+            unlit: true,
+            // The layers are far beyond the fog, and are blended with the sky by hand instead.
+            fog_enabled: false,
+            ..default()
+        });
+
+        commands.spawn((
+            LayerMaterial {
+                kind: layer.kind,
+                material: material.clone(),
+            },
+            Name::new("ParallaxLayer"),
+        ));
+
+        let mesh = match layer.kind {
+            LayerKind::Clouds => meshes.add(Sphere::new(1.).mesh().ico(2).unwrap()),
+            _ => meshes.add(outline_mesh(layer, &mut rng)),
+        };
+
+        // Clouds are scattered the same way on every tile, so that they line up when they wrap.
+        let clouds: Vec<Vec3> = (0..CLOUDS_PER_TILE)
+            .map(|_| {
+                Vec3::new(
+                    rng.random_range(0.0..layer.width),
+                    rng.random_range(-layer.roughness..layer.roughness),
+                    0.,
+                )
+            })
+            .collect();
+
+        for i in 0..TILES {
+            let x = (i as f32 - 1.) * layer.width;
+
+            let mut tile = commands.spawn((
+                Transform::from_xyz(x, 0., layer.z),
+                Visibility::default(),
+                ParallaxTile {
+                    factor: layer.factor,
+                    width: layer.width,
+                },
+                Name::new("ParallaxTile"),
+            ));
+
+            if layer.kind == LayerKind::Clouds {
+                tile.with_children(|parent| {
+                    for offset in &clouds {
+                        parent.spawn((
+                            Mesh3d(mesh.clone()),
+                            MeshMaterial3d(material.clone()),
+                            Transform::from_translation(*offset + Vec3::Y * layer.height)
+                                .with_scale(Vec3::new(8., 1.5, 1.)),
+                            NotShadowCaster,
+                            NotShadowReceiver,
+                        ));
+                    }
+                });
+            } else {
+                tile.insert((
+                    Mesh3d(mesh.clone()),
+                    MeshMaterial3d(material.clone()),
+                    NotShadowCaster,
+                    NotShadowReceiver,
+                ));
+            }
+        }
+    }
+}
+
+/// A flat outline of mountains or hills, facing the camera. The outline is made of waves that
+/// repeat exactly once per tile, so that tiles placed side by side line up seamlessly.
+fn outline_mesh(layer: &Layer, rng: &mut impl Rng) -> Mesh {
+    let waves: Vec<(f32, f32, f32)> = [1., 3., 7., 13.]
+        .into_iter()
+        .map(|frequency: f32| {
+            (
+                frequency,
+                layer.roughness / frequency.sqrt(),
+                rng.random_range(0.0..TAU),
+            )
+        })
+        .collect();
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut indices = vec![];
+
+    for i in 0..=OUTLINE_POINTS {
+        let t = i as f32 / OUTLINE_POINTS as f32;
+        let x = t * layer.width;
+        let y = layer.height
+            + waves
+                .iter()
+                .map(|(frequency, amplitude, phase)| {
+                    (t * frequency * TAU + phase).sin() * amplitude
+                })
+                .sum::<f32>();
+
+        positions.push([x, layer.bottom, 0.]);
+        positions.push([x, y.max(layer.bottom), 0.]);
+
+        if i < OUTLINE_POINTS {
+            let j = i * 2;
+            indices.extend_from_slice(&[j, j + 2, j + 1, j + 1, j + 2, j + 3]);
+        }
+    }
+
+    let normals = vec![[0., 0., 1.]; positions.len()];
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    );
+    mesh.insert_indices(Indices::U32(indices));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh
+}
+
+fn scroll_layers(
+    mut query: Query<(&mut Transform, &ParallaxTile)>,
+    time: Res<Time>,
+    speed: Res<Speed>,
+) {
+    for (mut transform, tile) in query.iter_mut() {
+        transform.translation.x -= time.delta_secs() * speed.current * tile.factor;
+
+        // Once a tile has scrolled out of view, move it to the far end of its layer.
+        if transform.translation.x < -tile.width * 1.5 {
+            transform.translation.x += tile.width * TILES as f32;
+        }
+    }
+}
+
+fn tint_layers(
+    query: Query<&LayerMaterial>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    biome: Res<CurrentBiome>,
+    time_of_day: Res<TimeOfDay>,
+    time: Res<Time<Real>>,
+) {
+    let t = 1. - (-TINT_RATE * time.delta_secs()).exp();
+
+    for layer in &query {
+        let target = layer.kind.color(&biome, &time_of_day);
+
+        // Only touch the material when the tint changes, as doing so uploads it again.
+        let Some(current) = materials.get(&layer.material).map(|m| m.base_color) else {
+            continue;
+        };
+        if current == target {
+            continue;
+        }
+
+        let next = if current.to_linear().distance(&target.to_linear()) < TINT_SNAP_DISTANCE {
+            target
+        } else {
+            current.mix(&target, t)
+        };
+
+        if let Some(material) = materials.get_mut(&layer.material) {
+            material.base_color = next;
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! Small bursts of particles: feathers puffing off the birb as it flaps, sparkles when it scores,
+//! a trail streaming behind it as it speeds up and feathers and debris when it crashes.
+//!
+//! Particles are ordinary entities moved around on the CPU, and every particle of a kind shares
+//! the same mesh and material, so they need nothing beyond what the rest of the game already
+//! uses.
+
+use std::{f32::consts::TAU, ops::Range};
+
+use bevy::{light::NotShadowCaster, prelude::*};
+use rand::prelude::*;
+
+use crate::{
+    difficulty::ActiveDifficulty, Action, AppState, Birb, InRun, ScoreCollider, Speed, Used,
+    GRAVITY,
+};
+
+/// Bursts are cut short rather than spawning more particles than this.
+const MAX_PARTICLES: usize = 400;
+
+const FLAP_PUFF: usize = 3;
+const SCORE_SPARKLES: usize = 12;
+/// How many trail particles stream off the birb per second at the max speed.
+const TRAIL_RATE: f32 = 30.;
+/// The trail only appears once the birb has built up this much of its speed.
+const TRAIL_MIN_SPEED_UP: f32 = 0.25;
+
+pub struct ParticlePlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleKind {
+    /// A few feathers shaken loose by a flap.
+    Puff,
+    /// Feathers knocked loose in a crash.
+    Feather,
+    Sparkle,
+    Trail,
+    Debris,
+}
+impl ParticleKind {
+    fn speed(&self) -> Range<f32> {
+        match self {
+            ParticleKind::Puff => 0.8..1.6,
+            ParticleKind::Feather => 2.0..4.0,
+            ParticleKind::Sparkle => 1.5..3.5,
+            ParticleKind::Trail => 0.0..0.2,
+            ParticleKind::Debris => 3.0..6.0,
+        }
+    }
+
+    fn lifetime(&self) -> Range<f32> {
+        match self {
+            ParticleKind::Puff => 0.5..0.9,
+            ParticleKind::Feather => 1.5..2.5,
+            ParticleKind::Sparkle => 0.4..0.7,
+            ParticleKind::Trail => 0.3..0.5,
+            ParticleKind::Debris => 1.5..2.5,
+        }
+    }
+
+    /// Pulls particles down, in units per second squared. Sparkles float upwards.
+    fn gravity(&self) -> f32 {
+        match self {
+            // Feathers catch the air, so they fall much more slowly than anything else.
+            ParticleKind::Puff | ParticleKind::Feather => 1.5,
+            ParticleKind::Sparkle => -0.5,
+            ParticleKind::Trail => 0.,
+            ParticleKind::Debris => GRAVITY,
+        }
+    }
+
+    /// How much of their velocity particles lose per second.
+    fn drag(&self) -> f32 {
+        match self {
+            ParticleKind::Puff | ParticleKind::Feather => 2.5,
+            ParticleKind::Sparkle => 3.,
+            ParticleKind::Trail => 0.,
+            ParticleKind::Debris => 0.5,
+        }
+    }
+
+    fn spin(&self) -> f32 {
+        match self {
+            ParticleKind::Puff | ParticleKind::Feather | ParticleKind::Debris => 6.,
+            ParticleKind::Sparkle | ParticleKind::Trail => 0.,
+        }
+    }
+
+    /// Whether the particles are left behind as the birb flies on, rather than keeping up with it.
+    fn left_behind(&self) -> bool {
+        match self {
+            ParticleKind::Puff | ParticleKind::Sparkle | ParticleKind::Trail => true,
+            // Crashes bring everything to a stop.
+            ParticleKind::Feather | ParticleKind::Debris => false,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec3,
+    gravity: f32,
+    drag: f32,
+    spin: Vec3,
+    left_behind: bool,
+    lifetime: Timer,
+}
+
+#[derive(Resource)]
+pub struct ParticleAssets {
+    feather: Handle<Mesh>,
+    sparkle: Handle<Mesh>,
+    trail: Handle<Mesh>,
+    debris: Handle<Mesh>,
+    feather_material: Handle<StandardMaterial>,
+    sparkle_material: Handle<StandardMaterial>,
+    trail_material: Handle<StandardMaterial>,
+    debris_material: Handle<StandardMaterial>,
+}
+impl ParticleAssets {
+    fn get(&self, kind: ParticleKind) -> (&Handle<Mesh>, &Handle<StandardMaterial>) {
+        match kind {
+            ParticleKind::Puff | ParticleKind::Feather => (&self.feather, &self.feather_material),
+            ParticleKind::Sparkle => (&self.sparkle, &self.sparkle_material),
+            ParticleKind::Trail => (&self.trail, &self.trail_material),
+            ParticleKind::Debris => (&self.debris, &self.debris_material),
+        }
+    }
+}
+impl FromWorld for ParticleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let feather = meshes.add(Cuboid::new(0.14, 0.02, 0.06));
+        let sparkle = meshes.add(Sphere::new(0.05).mesh().ico(0).unwrap());
+        let trail = meshes.add(Sphere::new(0.06).mesh().ico(0).unwrap());
+        let debris = meshes.add(Cuboid::from_length(0.12));
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let feather_material = materials.add(Color::srgb(1., 0.97, 0.9));
+        let sparkle_material = materials.add(StandardMaterial {
+            base_color: Color::srgb(1., 0.9, 0.3),
+            emissive: LinearRgba::rgb(2., 1.6, 0.4),
+            unlit: true,
+            ..default()
+        });
+        let trail_material = materials.add(StandardMaterial {
+            base_color: Color::srgba(1., 1., 1., 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
+        let debris_material = materials.add(StandardMaterial {
+            base_color: Color::srgb(0.35, 0.3, 0.28),
+            perceptual_roughness: 1.,
+            ..default()
+        });
+
+        Self {
+            feather,
+            sparkle,
+            trail,
+            debris,
+            feather_material,
+            sparkle_material,
+            trail_material,
+            debris_material,
+        }
+    }
+}
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticleAssets>();
+        app.add_systems(
+            Update,
+            (flap_puffs, score_sparkles, speed_trail, leave_behind)
+                .run_if(in_state(AppState::Playing)),
+        );
+        app.add_systems(Update, update_particles.run_if(in_state(InRun)));
+    }
+}
+
+/// Spawns `count` particles of `kind` at `position`, flying off in random directions that lean
+/// towards `direction`.
+pub fn burst(
+    commands: &mut Commands,
+    assets: &ParticleAssets,
+    kind: ParticleKind,
+    position: Vec3,
+    direction: Vec3,
+    count: usize,
+) {
+    let (mesh, material) = assets.get(kind);
+    let mut rng = rand::rng();
+
+    for _ in 0..count {
+        let spread = Vec3::new(
+            rng.random_range(-1.0..=1.0),
+            rng.random_range(-1.0..=1.0),
+            rng.random_range(-1.0..=1.0),
+        );
+        let velocity = (spread + direction).normalize_or(Vec3::Y) * rng.random_range(kind.speed());
+        let spin = kind.spin();
+
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(position).with_rotation(Quat::from_euler(
+                EulerRot::XYZ,
+                rng.random_range(0.0..TAU),
+                rng.random_range(0.0..TAU),
+                rng.random_range(0.0..TAU),
+            )),
+            Particle {
+                velocity,
+                gravity: kind.gravity(),
+                drag: kind.drag(),
+                spin: Vec3::new(
+                    rng.random_range(-spin..=spin),
+                    rng.random_range(-spin..=spin),
+                    rng.random_range(-spin..=spin),
+                ),
+                left_behind: kind.left_behind(),
+                lifetime: Timer::from_seconds(rng.random_range(kind.lifetime()), TimerMode::Once),
+            },
+            NotShadowCaster,
+            Name::new("Particle"),
+            DespawnOnExit(InRun),
+        ));
+    }
+}
+
+/// How many more particles can be spawned right now.
+fn room(particles: &Query<(), With<Particle>>) -> usize {
+    MAX_PARTICLES.saturating_sub(particles.iter().len())
+}
+
+fn flap_puffs(
+    mut commands: Commands,
+    mut events: MessageReader<Action>,
+    birb_query: Query<&Transform, With<Birb>>,
+    particles: Query<(), With<Particle>>,
+    assets: Res<ParticleAssets>,
+) {
+    let Ok(birb) = birb_query.single() else {
+        return;
+    };
+
+    let mut room = room(&particles);
+
+    for e in events.read() {
+        // Feathers are shaken loose in the opposite direction to the flap.
+        let direction = match e {
+            Action::BirbUp => Vec3::NEG_Y,
+            Action::BirbDown => Vec3::Y,
+            _ => continue,
+        };
+
+        let count = FLAP_PUFF.min(room);
+        room -= count;
+
+        burst(
+            &mut commands,
+            &assets,
+            ParticleKind::Puff,
+            birb.translation,
+            direction,
+            count,
+        );
+    }
+}
+
+fn score_sparkles(
+    mut commands: Commands,
+    query: Query<&GlobalTransform, (With<ScoreCollider>, Added<Used>)>,
+    particles: Query<(), With<Particle>>,
+    assets: Res<ParticleAssets>,
+) {
+    let mut room = room(&particles);
+
+    for transform in &query {
+        let count = SCORE_SPARKLES.min(room);
+        room -= count;
+
+        burst(
+            &mut commands,
+            &assets,
+            ParticleKind::Sparkle,
+            transform.translation(),
+            Vec3::ZERO,
+            count,
+        );
+    }
+}
+
+/// Streams a trail off the back of the birb, thickening as it speeds up.
+fn speed_trail(
+    mut commands: Commands,
+    mut pending: Local<f32>,
+    birb_query: Query<&Transform, With<Birb>>,
+    particles: Query<(), With<Particle>>,
+    assets: Res<ParticleAssets>,
+    speed: Res<Speed>,
+    difficulty: Res<ActiveDifficulty>,
+    time: Res<Time>,
+) {
+    let Ok(birb) = birb_query.single() else {
+        return;
+    };
+
+    let speed_up = speed.build_up(difficulty.preset.start_speed);
+    if speed_up < TRAIL_MIN_SPEED_UP {
+        *pending = 0.;
+        return;
+    }
+
+    *pending += TRAIL_RATE * speed_up * time.delta_secs();
+    let count = (*pending as usize).min(room(&particles));
+    *pending = pending.fract();
+
+    burst(
+        &mut commands,
+        &assets,
+        ParticleKind::Trail,
+        birb.translation + Vec3::NEG_X * 0.4,
+        Vec3::ZERO,
+        count,
+    );
+}
+
+/// Scrolls particles by along with the ground.
+fn leave_behind(mut query: Query<(&mut Transform, &Particle)>, speed: Res<Speed>, time: Res<Time>) {
+    for (mut transform, particle) in query.iter_mut() {
+        if particle.left_behind {
+            transform.translation.x -= speed.current * time.delta_secs();
+        }
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut Particle)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut transform, mut particle) in query.iter_mut() {
+        if particle.lifetime.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let drag = particle.velocity * particle.drag * dt;
+        particle.velocity -= drag;
+        particle.velocity.y -= particle.gravity * dt;
+
+        transform.translation += particle.velocity * dt;
+        transform.translation.y = transform.translation.y.max(0.);
+        transform.rotate(Quat::from_scaled_axis(particle.spin * dt));
+
+        // Shrink away towards the end rather than popping out of existence.
+        transform.scale = Vec3::splat(particle.lifetime.fraction_remaining().min(0.25) * 4.);
+    }
+}
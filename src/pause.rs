@@ -0,0 +1,270 @@
+use bevy::{color::palettes::css::LIME, prelude::*, window::WindowFocused};
+
+use crate::{
+    typing::{Suspended, TypingTarget},
+    ui::FONT_SIZE,
+    Action, AppState, FontAssets, MusicController,
+};
+
+/// How long the countdown lasts when resuming, so that players have a moment to get their
+/// fingers back on the keys.
+const COUNTDOWN_SECS: f32 = 3.;
+
+pub struct PausePlugin;
+
+/// Counts down before play resumes. Uses real time, because virtual time is paused.
+#[derive(Resource)]
+struct ResumeCountdown(Timer);
+
+/// Set when the player restarts from the pause menu, so that the next run starts without a trip
+/// through the start screen.
+#[derive(Resource, Default)]
+struct RestartRequested(bool);
+
+#[derive(Component)]
+struct PauseMenu;
+#[derive(Component)]
+struct CountdownText;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RestartRequested>();
+
+        app.add_systems(
+            Update,
+            pause_on_escape_or_focus_loss.run_if(in_state(AppState::Playing)),
+        );
+        app.add_systems(OnEnter(AppState::Paused), (pause, spawn_pause_menu));
+        app.add_systems(OnExit(AppState::Paused), unpause);
+        app.add_systems(
+            Update,
+            (pause_menu_actions, resume_on_escape, countdown).run_if(in_state(AppState::Paused)),
+        );
+        app.add_systems(
+            Update,
+            restart_immediately.run_if(in_state(AppState::StartScreen)),
+        );
+    }
+}
+
+fn pause_on_escape_or_focus_loss(
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut focus_events: MessageReader<WindowFocused>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let lost_focus = focus_events.read().any(|event| !event.focused);
+
+    if keyboard.just_pressed(KeyCode::Escape) || lost_focus {
+        next_state.set(AppState::Paused);
+        keyboard.clear();
+    }
+}
+
+fn pause(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    music_query: Query<&AudioSink, With<MusicController>>,
+    typing_query: Query<Entity, With<TypingTarget>>,
+) {
+    time.pause();
+
+    for sink in &music_query {
+        sink.pause();
+    }
+
+    // Keep the words in the top and bottom bars from reacting to pause menu commands.
+    for entity in &typing_query {
+        commands.entity(entity).insert(Suspended);
+    }
+}
+
+fn unpause(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    music_query: Query<&AudioSink, With<MusicController>>,
+    typing_query: Query<Entity, With<Suspended>>,
+) {
+    time.unpause();
+
+    for sink in &music_query {
+        sink.play();
+    }
+
+    for entity in &typing_query {
+        commands.entity(entity).remove::<Suspended>();
+    }
+
+    commands.remove_resource::<ResumeCountdown>();
+}
+
+fn spawn_pause_menu(mut commands: Commands, font_assets: Res<FontAssets>) {
+    let text_font = TextFont {
+        font: font_assets.main.clone(),
+        font_size: FONT_SIZE,
+        ..default()
+    };
+
+    let container = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.5)),
+            PauseMenu,
+            Name::new("PauseMenu"),
+            DespawnOnExit(AppState::Paused),
+        ))
+        .id();
+
+    let bg = commands
+        .spawn((
+            Node {
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                ..default()
+            },
+            BackgroundColor(Color::BLACK),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("PAUSED"),
+            text_font.clone(),
+            TextColor(Color::WHITE),
+        ))
+        .id();
+
+    let commands_list = [
+        ("resume", Action::Resume),
+        ("restart", Action::Restart),
+        ("menu", Action::Quit),
+    ]
+    .map(|(word, action)| {
+        commands
+            .spawn((
+                Text::default(),
+                text_font.clone(),
+                TextColor(LIME.into()),
+                TypingTarget::new_whole(word.into(), vec![action]),
+            ))
+            .with_child((
+                TextSpan::new(word.to_uppercase()),
+                text_font.clone(),
+                TextColor(Color::srgb_u8(255, 235, 146)),
+            ))
+            .id()
+    });
+
+    commands.entity(container).add_children(&[bg]);
+    commands.entity(bg).add_children(&[title]);
+    commands.entity(bg).add_children(&commands_list);
+}
+
+fn pause_menu_actions(
+    mut commands: Commands,
+    mut events: MessageReader<Action>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut restart: ResMut<RestartRequested>,
+    menu_query: Query<Entity, With<PauseMenu>>,
+    font_assets: Res<FontAssets>,
+) {
+    for e in events.read() {
+        match e {
+            Action::Resume => {
+                for entity in &menu_query {
+                    commands.entity(entity).despawn();
+                }
+                start_countdown(&mut commands, &font_assets);
+            }
+            Action::Restart => {
+                restart.0 = true;
+                next_state.set(AppState::StartScreen);
+            }
+            Action::Quit => {
+                next_state.set(AppState::StartScreen);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn resume_on_escape(
+    mut commands: Commands,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    menu_query: Query<Entity, With<PauseMenu>>,
+    font_assets: Res<FontAssets>,
+) {
+    if !keyboard.just_pressed(KeyCode::Escape) || menu_query.is_empty() {
+        return;
+    }
+
+    for entity in &menu_query {
+        commands.entity(entity).despawn();
+    }
+    start_countdown(&mut commands, &font_assets);
+
+    keyboard.clear();
+}
+
+fn start_countdown(commands: &mut Commands, font_assets: &FontAssets) {
+    commands.insert_resource(ResumeCountdown(Timer::from_seconds(
+        COUNTDOWN_SECS,
+        TimerMode::Once,
+    )));
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Children::spawn(Spawn((
+            Text::new(format!("{}", COUNTDOWN_SECS.ceil())),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE * 3.,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            CountdownText,
+        ))),
+        DespawnOnExit(AppState::Paused),
+    ));
+}
+
+fn countdown(
+    mut countdown: Option<ResMut<ResumeCountdown>>,
+    mut text_query: Query<&mut Text, With<CountdownText>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    time: Res<Time<Real>>,
+) {
+    let Some(countdown) = countdown.as_mut() else {
+        return;
+    };
+
+    if countdown.0.tick(time.delta()).just_finished() {
+        next_state.set(AppState::Playing);
+        return;
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.0 = format!("{}", countdown.0.remaining_secs().ceil());
+    }
+}
+
+fn restart_immediately(mut restart: ResMut<RestartRequested>, mut events: MessageWriter<Action>) {
+    if restart.0 {
+        restart.0 = false;
+        events.write(Action::Start);
+    }
+}
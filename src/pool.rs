@@ -0,0 +1,13 @@
+//! Obstacles and ground chunks are recycled once they've scrolled out of view, rather than being
+//! despawned and built again from scratch. Over a long run, this keeps the number of entities
+//! and assets steady.
+//!
+//! A recycled entity loses the marker that makes it part of the world, like
+//! [`Obstacle`](crate::Obstacle) or [`Ground`](crate::ground::Ground), and is hidden away until
+//! it's needed again.
+
+use bevy::prelude::*;
+
+/// Marks an entity that's waiting to be reused.
+#[derive(Component)]
+pub struct Pooled;
@@ -0,0 +1,103 @@
+//! Score popups that float up from the spot where points were scored.
+
+use bevy::prelude::*;
+
+use crate::InRun;
+
+/// How long a popup stays on screen.
+const POPUP_SECS: f32 = 0.9;
+/// How far a popup floats upwards over its lifetime, in world units.
+const POPUP_RISE: f32 = 1.2;
+const POPUP_FONT_SIZE: f32 = 28.;
+
+pub struct PopupPlugin;
+
+/// A bit of text that follows a point in the world, rising and fading away.
+#[derive(Component)]
+pub struct Popup {
+    position: Vec3,
+    timer: Timer,
+}
+
+impl Plugin for PopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, float_popups);
+    }
+}
+
+/// A popup showing `text` at `position` in the world.
+pub fn popup(
+    text: impl Into<String>,
+    color: Color,
+    position: Vec3,
+    font: Handle<Font>,
+) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font,
+            font_size: POPUP_FONT_SIZE,
+            ..default()
+        },
+        TextColor(color),
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        // Hidden until it's been placed, so it doesn't flash in the corner of the screen.
+        Visibility::Hidden,
+        Popup {
+            position,
+            timer: Timer::from_seconds(POPUP_SECS, TimerMode::Once),
+        },
+        Name::new("Popup"),
+        DespawnOnExit(InRun),
+    )
+}
+
+fn float_popups(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Popup,
+        &mut Node,
+        &mut TextColor,
+        &mut Visibility,
+        &ComputedNode,
+    )>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    ui_scale: Res<UiScale>,
+    time: Res<Time>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.single() else {
+        return;
+    };
+
+    for (entity, mut popup, mut node, mut color, mut visibility, computed) in query.iter_mut() {
+        popup.timer.tick(time.delta());
+
+        if popup.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let position = popup.position + Vec3::Y * POPUP_RISE * popup.timer.fraction();
+        let Ok(viewport) = camera.world_to_viewport(camera_transform, position) else {
+            continue;
+        };
+
+        // Center the text on its position. Node sizes and offsets are scaled by `UiScale`, but
+        // the viewport position isn't.
+        let size = computed.size() * computed.inverse_scale_factor();
+        let offset = viewport / ui_scale.0 - size / 2.;
+        node.left = Val::Px(offset.x);
+        node.top = Val::Px(offset.y);
+
+        color.0.set_alpha(popup.timer.fraction_remaining());
+
+        // The text has no size until it's been laid out.
+        if size != Vec2::ZERO {
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
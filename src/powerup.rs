@@ -0,0 +1,359 @@
+use bevy::{
+    color::palettes::css::{DEEP_SKY_BLUE, GOLD, ORANGE_RED, TURQUOISE},
+    math::{
+        bounding::{Aabb3d, BoundingVolume, IntersectsVolume},
+        Vec3A,
+    },
+    prelude::*,
+};
+use rand::prelude::*;
+
+use crate::{
+    audio::sound_effect, loading::AudioAssets, luck::Seed, spawn_obstacle, typing::TypingTarget,
+    Action, AppState, Birb, DistanceToSpawn, HitBox, InRun, ObstacleSpacing, ScoreCollider,
+    Scrolling, Speed, TargetPosition, BIRB_MAX_Y, BIRB_MIN_Y,
+};
+
+/// The chance that a power-up appears between any two obstacles.
+const SPAWN_CHANCE: f32 = 0.35;
+/// How far above or below the previous gap a power-up may appear.
+const SPAWN_SPREAD: f32 = 1.;
+
+const SLOW_MOTION_SECS: f32 = 4.;
+const SLOW_MOTION_FACTOR: f32 = 0.6;
+const MAGNET_SECS: f32 = 5.;
+const MAGNET_KEYSTROKE_SECS: f32 = 0.3;
+const DOUBLE_SCORE_SECS: f32 = 8.;
+/// After the shield absorbs a hit, the birb can't be hurt for a moment so that it has time to
+/// clear the obstacle.
+const SHIELD_GRACE_SECS: f32 = 1.;
+
+pub struct PowerUpPlugin;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUp {
+    SlowMotion,
+    Shield,
+    Magnet,
+    DoubleScore,
+}
+impl PowerUp {
+    const ALL: [PowerUp; 4] = [
+        PowerUp::SlowMotion,
+        PowerUp::Shield,
+        PowerUp::Magnet,
+        PowerUp::DoubleScore,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerUp::SlowMotion => "SLOW",
+            PowerUp::Shield => "SHIELD",
+            PowerUp::Magnet => "MAGNET",
+            PowerUp::DoubleScore => "2X",
+        }
+    }
+
+    fn pickup_sound(&self, audio_assets: &AudioAssets) -> Handle<AudioSource> {
+        match self {
+            PowerUp::SlowMotion => audio_assets.slow_motion_pickup.clone(),
+            PowerUp::Shield => audio_assets.shield_pickup.clone(),
+            PowerUp::Magnet => audio_assets.magnet_pickup.clone(),
+            PowerUp::DoubleScore => audio_assets.double_score_pickup.clone(),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct PowerUpAssets {
+    meshes: [Handle<Mesh>; 4],
+    materials: [Handle<StandardMaterial>; 4],
+}
+impl FromWorld for PowerUpAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let meshes = [
+            meshes.add(Torus::new(0.15, 0.35)),
+            meshes.add(Sphere::new(0.3)),
+            meshes.add(Capsule3d::new(0.15, 0.4)),
+            meshes.add(Cuboid::from_length(0.45)),
+        ];
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let materials = [DEEP_SKY_BLUE, TURQUOISE, ORANGE_RED, GOLD].map(|color| {
+            materials.add(StandardMaterial {
+                base_color: color.into(),
+                emissive: LinearRgba::from(color) * 0.5,
+                ..default()
+            })
+        });
+
+        Self { meshes, materials }
+    }
+}
+
+/// The power-ups currently affecting the birb and how long they have left.
+#[derive(Resource, Default)]
+pub struct ActivePowerUps {
+    pub slow_motion: Option<Timer>,
+    /// How much speed the slow-motion power-up took away, so that it can be given back.
+    slowed_by: f32,
+    pub shield: bool,
+    shield_grace: Option<Timer>,
+    pub magnet: Option<Timer>,
+    magnet_keystroke: Timer,
+    pub double_score: Option<Timer>,
+}
+impl ActivePowerUps {
+    pub fn score_multiplier(&self) -> u32 {
+        if self.double_score.is_some() {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Returns `true` if a hit was absorbed by the shield rather than ending the run.
+    pub fn absorb_hit(&mut self) -> bool {
+        if self.shield_grace.is_some() {
+            return true;
+        }
+
+        if !self.shield {
+            return false;
+        }
+
+        self.shield = false;
+        self.shield_grace = Some(Timer::from_seconds(SHIELD_GRACE_SECS, TimerMode::Once));
+        true
+    }
+}
+
+#[derive(Resource)]
+struct PowerUpRng(StdRng);
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PowerUpAssets>()
+            .init_resource::<ActivePowerUps>();
+
+        app.add_systems(OnExit(AppState::StartScreen), reset_power_ups);
+        app.add_systems(
+            Update,
+            (
+                spawn_power_up.after(spawn_obstacle),
+                pick_up_power_ups,
+                tick_power_ups,
+                magnet,
+            )
+                .run_if(in_state(AppState::Playing)),
+        );
+        app.add_systems(Update, power_up_movement.run_if(in_state(Scrolling)));
+    }
+}
+
+fn reset_power_ups(mut commands: Commands, seed: Res<Seed>) {
+    commands.insert_resource(ActivePowerUps::default());
+    commands.insert_resource(PowerUpRng(StdRng::seed_from_u64(seed.0)));
+}
+
+/// Sometimes spawns a power-up halfway between two obstacles, near the height of the previous
+/// gap so that it's within reach.
+fn spawn_power_up(
+    mut commands: Commands,
+    distance: Res<DistanceToSpawn>,
+    spacing: Res<ObstacleSpacing>,
+    score_collider_query: Query<&GlobalTransform, With<ScoreCollider>>,
+    assets: Res<PowerUpAssets>,
+    mut rng: ResMut<PowerUpRng>,
+    mut spawned: Local<bool>,
+) {
+    if distance.0 > spacing.0 / 2. {
+        *spawned = false;
+        return;
+    }
+
+    if *spawned {
+        return;
+    }
+    *spawned = true;
+
+    let Some(previous_gap_y) = score_collider_query
+        .iter()
+        .map(|t| t.translation())
+        .max_by(|a, b| a.x.partial_cmp(&b.x).unwrap())
+        .map(|t| t.y)
+    else {
+        return;
+    };
+
+    let rng = &mut rng.0;
+
+    if rng.random::<f32>() >= SPAWN_CHANCE {
+        return;
+    }
+
+    let kind = *PowerUp::ALL.choose(rng).unwrap();
+    let y = (previous_gap_y + rng.random_range(-SPAWN_SPREAD..SPAWN_SPREAD))
+        .clamp(BIRB_MIN_Y, BIRB_MAX_Y);
+
+    commands.spawn((
+        Mesh3d(assets.meshes[kind as usize].clone()),
+        MeshMaterial3d(assets.materials[kind as usize].clone()),
+        Transform::from_xyz(38., y, 0.),
+        HitBox(Aabb3d::new(Vec3A::splat(0.), Vec3A::splat(0.35))),
+        kind,
+        Name::new("PowerUp"),
+        DespawnOnExit(InRun),
+    ));
+}
+
+fn power_up_movement(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform), With<PowerUp>>,
+    time: Res<Time>,
+    speed: Res<Speed>,
+) {
+    let delta = time.delta_secs() * speed.current;
+
+    for (entity, mut transform) in query.iter_mut() {
+        transform.translation.x -= delta;
+        transform.rotate_y(time.delta_secs() * 2.);
+
+        if transform.translation.x < -30. {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn pick_up_power_ups(
+    mut commands: Commands,
+    birb_query: Query<(&HitBox, &Transform), With<Birb>>,
+    power_up_query: Query<(Entity, &PowerUp, &HitBox, &Transform)>,
+    mut active: ResMut<ActivePowerUps>,
+    mut speed: ResMut<Speed>,
+    audio_assets: Res<AudioAssets>,
+) {
+    let Ok((birb_hitbox, transform)) = birb_query.single() else {
+        return;
+    };
+
+    let birb_aabb = birb_hitbox.0.translated_by(transform.translation);
+
+    for (entity, kind, hitbox, transform) in power_up_query.iter() {
+        if !hitbox
+            .0
+            .translated_by(transform.translation)
+            .intersects(&birb_aabb)
+        {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+
+        commands.spawn(sound_effect(kind.pickup_sound(&audio_assets)));
+
+        match kind {
+            PowerUp::SlowMotion => {
+                if active.slow_motion.is_none() {
+                    active.slowed_by = speed.current * (1. - SLOW_MOTION_FACTOR);
+                    speed.current -= active.slowed_by;
+                }
+                active.slow_motion = Some(Timer::from_seconds(SLOW_MOTION_SECS, TimerMode::Once));
+            }
+            PowerUp::Shield => active.shield = true,
+            PowerUp::Magnet => {
+                active.magnet = Some(Timer::from_seconds(MAGNET_SECS, TimerMode::Once));
+                active.magnet_keystroke =
+                    Timer::from_seconds(MAGNET_KEYSTROKE_SECS, TimerMode::Repeating);
+            }
+            PowerUp::DoubleScore => {
+                active.double_score = Some(Timer::from_seconds(DOUBLE_SCORE_SECS, TimerMode::Once))
+            }
+        }
+    }
+}
+
+fn tick_power_ups(mut active: ResMut<ActivePowerUps>, mut speed: ResMut<Speed>, time: Res<Time>) {
+    let delta = time.delta();
+
+    let active = &mut *active;
+
+    if active
+        .slow_motion
+        .as_mut()
+        .is_some_and(|timer| timer.tick(delta).is_finished())
+    {
+        active.slow_motion = None;
+        speed.increase(active.slowed_by);
+        active.slowed_by = 0.;
+    }
+
+    for timer in [
+        &mut active.shield_grace,
+        &mut active.magnet,
+        &mut active.double_score,
+    ] {
+        if timer
+            .as_mut()
+            .is_some_and(|timer| timer.tick(delta).is_finished())
+        {
+            *timer = None;
+        }
+    }
+}
+
+/// While the magnet is active, automatically types letters that pull the birb towards the middle
+/// of the next gap.
+fn magnet(
+    mut active: ResMut<ActivePowerUps>,
+    birb_query: Query<&TargetPosition, With<Birb>>,
+    score_collider_query: Query<&GlobalTransform, With<ScoreCollider>>,
+    mut typing_query: Query<(Entity, &mut TypingTarget)>,
+    mut events: MessageWriter<Action>,
+    time: Res<Time>,
+) {
+    if active.magnet.is_none() {
+        return;
+    }
+
+    if !active.magnet_keystroke.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let Ok(target) = birb_query.single() else {
+        return;
+    };
+
+    let Some(next_gap_y) = score_collider_query
+        .iter()
+        .map(|t| t.translation())
+        .filter(|t| t.x > -0.5)
+        .min_by(|a, b| a.x.partial_cmp(&b.x).unwrap())
+        .map(|t| t.y)
+    else {
+        return;
+    };
+
+    let diff = next_gap_y - target.0.y;
+    if diff.abs() <= 0.125 {
+        return;
+    }
+
+    let wanted = if diff > 0. {
+        Action::BirbUp
+    } else {
+        Action::BirbDown
+    };
+
+    for (entity, mut typing_target) in typing_query.iter_mut() {
+        let steers = typing_target
+            .letter_actions
+            .iter()
+            .any(|action| std::mem::discriminant(action) == std::mem::discriminant(&wanted));
+
+        if steers {
+            typing_target.complete_char(entity, &mut events);
+        }
+    }
+}
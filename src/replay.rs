@@ -0,0 +1,570 @@
+//! Replay files store everything needed to reproduce a run: the seed, the difficulty and typing
+//! mode the run was played with, whether it had weather and every keystroke, stamped with the time
+//! it was typed.
+//!
+//! Run with `--record <path>` to save each run to `path`, or `--replay <path>` to watch one.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput, NativeKeyCode},
+        ButtonState, InputSystems,
+    },
+    prelude::*,
+    time::TimeUpdateStrategy,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    difficulty::{apply_difficulty, start_run, Acceleration, ActiveDifficulty, DifficultyPreset},
+    gap_bag,
+    ghost::RunRecording,
+    luck::{NextGapBag, Seed},
+    reset,
+    settings::TypingMode,
+    typing::{apply_typing_mode, ActiveTypingMode},
+    weather::{apply_weather, start_weather, Weather},
+    Action, AppState, InRun, Score,
+};
+
+const MAGIC: &str = "typey_birb replay";
+pub const REPLAY_VERSION: u32 = 4;
+
+/// Recording and playback advance time by exactly this much every frame, regardless of the frame
+/// rate, so that a replay sees the same frames as the run it was recorded from.
+const TIMESTEP: f32 = 1. / 60.;
+
+/// How much faster the birb flew after each obstacle before difficulty presets, which version 1
+/// replays were recorded with.
+const LEGACY_ACCELERATION: f32 = 0.1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub score: u32,
+    pub timestep: f32,
+    pub difficulty: DifficultyPreset,
+    pub adaptive: bool,
+    pub weather: bool,
+    pub typing_mode: TypingMode,
+    pub keystrokes: Vec<(f32, char)>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    NotAReplay,
+    UnsupportedVersion(u32),
+    Malformed {
+        line: usize,
+    },
+    InvalidDifficulty {
+        line: usize,
+        err: ron::de::SpannedError,
+    },
+    Missing(&'static str),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "{err}"),
+            ReplayError::NotAReplay => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(version) => {
+                write!(f, "unsupported replay version {version}")
+            }
+            ReplayError::Malformed { line } => write!(f, "malformed replay on line {line}"),
+            ReplayError::InvalidDifficulty { line, err } => {
+                write!(f, "invalid difficulty on line {line}: {err}")
+            }
+            ReplayError::Missing(field) => write!(f, "replay is missing `{field}`"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+impl Replay {
+    pub fn from_recording(
+        recording: &RunRecording,
+        score: u32,
+        timestep: f32,
+        difficulty: &ActiveDifficulty,
+        weather: &Weather,
+        typing_mode: &ActiveTypingMode,
+    ) -> Self {
+        Self {
+            seed: recording.course.seed,
+            score,
+            timestep,
+            difficulty: difficulty.preset.clone(),
+            adaptive: difficulty.adaptive,
+            weather: weather.enabled,
+            typing_mode: typing_mode.0,
+            keystrokes: recording.keystrokes.clone(),
+        }
+    }
+
+    /// Serializes the replay into a line-based text format.
+    ///
+    /// ```text
+    /// typey_birb replay 4
+    /// seed 1234
+    /// score 56
+    /// timestep 0.016666668
+    /// difficulty (start_speed:2.0,max_speed:4.4,acceleration:Linear,...)
+    /// adaptive false
+    /// weather true
+    /// typing_mode strict
+    /// key 0.532 a
+    /// ```
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{MAGIC} {REPLAY_VERSION}\n");
+        text += &format!("seed {}\n", self.seed);
+        text += &format!("score {}\n", self.score);
+        text += &format!("timestep {}\n", self.timestep);
+        // Presets serialize to a single line with the default ron config.
+        text += &format!(
+            "difficulty {}\n",
+            ron::to_string(&self.difficulty).expect("difficulty presets are serializable")
+        );
+        text += &format!("adaptive {}\n", self.adaptive);
+        text += &format!("weather {}\n", self.weather);
+        let typing_mode = match self.typing_mode {
+            TypingMode::Forgiving => "forgiving",
+            TypingMode::Strict => "strict",
+        };
+        text += &format!("typing_mode {typing_mode}\n");
+        for (time, char) in &self.keystrokes {
+            text += &format!("key {time} {char}\n");
+        }
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, ReplayError> {
+        let mut lines = text.lines().enumerate();
+
+        let version = lines
+            .next()
+            .and_then(|(_, line)| line.strip_prefix(MAGIC))
+            .ok_or(ReplayError::NotAReplay)?;
+        let version = version
+            .trim()
+            .parse()
+            .map_err(|_| ReplayError::Malformed { line: 1 })?;
+        if version == 0 || version > REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let mut seed = None;
+        let mut score = None;
+        let mut timestep = None;
+        let mut difficulty = None;
+        let mut adaptive = false;
+        // Replays from before weather events never had any.
+        let mut weather = false;
+        // Replays from before the typing mode was recorded play back in the default mode.
+        let mut typing_mode = TypingMode::Forgiving;
+        let mut speed = None;
+        let mut spacing = None;
+        let mut keystrokes = vec![];
+
+        for (index, line) in lines {
+            let malformed = ReplayError::Malformed { line: index + 1 };
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((field, value)) = line.split_once(' ') else {
+                return Err(malformed);
+            };
+
+            match field {
+                "seed" => seed = Some(value.parse().map_err(|_| malformed)?),
+                "score" => score = Some(value.parse().map_err(|_| malformed)?),
+                "timestep" => timestep = Some(value.parse().map_err(|_| malformed)?),
+                "difficulty" => {
+                    let parsed =
+                        ron::from_str(value).map_err(|err| ReplayError::InvalidDifficulty {
+                            line: index + 1,
+                            err,
+                        })?;
+                    difficulty = Some(parsed);
+                }
+                "adaptive" => adaptive = value.parse().map_err(|_| malformed)?,
+                "weather" => weather = value.parse().map_err(|_| malformed)?,
+                "typing_mode" => {
+                    typing_mode = match value {
+                        "forgiving" => TypingMode::Forgiving,
+                        "strict" => TypingMode::Strict,
+                        _ => return Err(malformed),
+                    }
+                }
+                // Version 1 replays store their speed and spacing instead of a difficulty.
+                "speed" => {
+                    let parsed = value
+                        .split_once(' ')
+                        .and_then(|(speed, max)| Some((speed.parse().ok()?, max.parse().ok()?)));
+                    speed = Some(parsed.ok_or(malformed)?);
+                }
+                "spacing" => spacing = Some(value.parse().map_err(|_| malformed)?),
+                "key" => {
+                    // The character comes last so that a space can be stored without escaping.
+                    let parsed = value.split_once(' ').and_then(|(time, char)| {
+                        let mut chars = char.chars();
+                        let char = chars.next().filter(|_| chars.next().is_none())?;
+                        Some((time.parse().ok()?, char))
+                    });
+                    keystrokes.push(parsed.ok_or(malformed)?);
+                }
+                // Ignore unknown fields so that newer additions don't break older replays.
+                _ => {}
+            }
+        }
+
+        let difficulty = if version == 1 {
+            let (speed, max_speed): (f32, f32) = speed.ok_or(ReplayError::Missing("speed"))?;
+            let spacing = spacing.ok_or(ReplayError::Missing("spacing"))?;
+
+            DifficultyPreset {
+                start_speed: speed,
+                max_speed,
+                acceleration: Acceleration::Linear,
+                obstacles_to_max_speed: ((max_speed - speed) / LEGACY_ACCELERATION).round() as u32,
+                start_spacing: spacing,
+                end_spacing: spacing,
+                obstacles_to_end_spacing: 0,
+                gap_variance: 1.,
+            }
+        } else {
+            difficulty.ok_or(ReplayError::Missing("difficulty"))?
+        };
+
+        Ok(Self {
+            seed: seed.ok_or(ReplayError::Missing("seed"))?,
+            score: score.ok_or(ReplayError::Missing("score"))?,
+            timestep: timestep.ok_or(ReplayError::Missing("timestep"))?,
+            difficulty,
+            adaptive,
+            weather,
+            typing_mode,
+            keystrokes,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        Self::from_text(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        Ok(fs::write(path, self.to_text())?)
+    }
+}
+
+#[derive(Resource)]
+struct RecordTo {
+    path: PathBuf,
+    /// The time every recorded frame advanced by.
+    timestep: f32,
+}
+
+#[derive(Resource)]
+struct Playback {
+    replay: Replay,
+    cursor: usize,
+}
+
+pub enum ReplayPlugin {
+    Disabled,
+    Record(PathBuf),
+    Playback(PathBuf),
+}
+
+impl ReplayPlugin {
+    /// Reads `--record <path>` or `--replay <path>` from the command line.
+    pub fn from_args() -> Self {
+        let mut args = std::env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            let plugin: fn(PathBuf) -> Self = match arg.as_str() {
+                "--record" => ReplayPlugin::Record,
+                "--replay" => ReplayPlugin::Playback,
+                _ => continue,
+            };
+
+            if let Some(path) = args.next() {
+                return plugin(path.into());
+            }
+        }
+
+        ReplayPlugin::Disabled
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match self {
+            ReplayPlugin::Disabled => {}
+            ReplayPlugin::Record(path) => {
+                app.insert_resource(RecordTo {
+                    path: path.clone(),
+                    timestep: TIMESTEP,
+                });
+                app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                    TIMESTEP,
+                )));
+                app.add_systems(OnEnter(AppState::EndScreen), save_replay);
+            }
+            ReplayPlugin::Playback(path) => {
+                let replay = match Replay::load(path) {
+                    Ok(replay) => replay,
+                    Err(err) => {
+                        error!("Failed to load replay {}: {err}", path.display());
+                        return;
+                    }
+                };
+
+                let seed = Seed(replay.seed);
+                app.insert_resource(seed);
+                app.insert_resource(gap_bag(&seed));
+                app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                    replay.timestep,
+                )));
+                app.insert_resource(Playback { replay, cursor: 0 });
+
+                app.add_systems(Update, auto_start.run_if(in_state(AppState::StartScreen)));
+                app.add_systems(
+                    OnExit(AppState::StartScreen),
+                    start_playback
+                        .after(apply_difficulty)
+                        .after(apply_weather)
+                        .after(apply_typing_mode),
+                );
+                app.add_systems(
+                    PreUpdate,
+                    feed_keystrokes
+                        .after(InputSystems)
+                        .run_if(in_state(AppState::Playing)),
+                );
+                app.add_systems(OnEnter(AppState::EndScreen), check_playback);
+                // Every run of the replay flies its course, rather than a new one.
+                app.add_systems(OnExit(InRun), restore_course.after(reset));
+            }
+        }
+    }
+}
+
+fn save_replay(
+    recording: Res<RunRecording>,
+    score: Res<Score>,
+    difficulty: Res<ActiveDifficulty>,
+    weather: Res<Weather>,
+    typing_mode: Res<ActiveTypingMode>,
+    record_to: Res<RecordTo>,
+) {
+    let replay = Replay::from_recording(
+        &recording,
+        score.0,
+        record_to.timestep,
+        &difficulty,
+        &weather,
+        &typing_mode,
+    );
+
+    match replay.save(&record_to.path) {
+        Ok(()) => info!("Saved replay to {}", record_to.path.display()),
+        Err(err) => error!("Failed to save replay {}: {err}", record_to.path.display()),
+    }
+}
+
+fn auto_start(mut events: MessageWriter<Action>) {
+    events.write(Action::Start);
+}
+
+fn start_playback(
+    mut commands: Commands,
+    mut playback: ResMut<Playback>,
+    mut bag: ResMut<NextGapBag>,
+    seed: Res<Seed>,
+) {
+    playback.cursor = 0;
+
+    let replay = &playback.replay;
+    start_run(&mut commands, &mut bag, &replay.difficulty, replay.adaptive);
+    start_weather(&mut commands, &seed, replay.weather);
+    commands.insert_resource(ActiveTypingMode(replay.typing_mode));
+}
+
+fn restore_course(mut commands: Commands, playback: Res<Playback>) {
+    let seed = Seed(playback.replay.seed);
+    commands.insert_resource(seed);
+    commands.insert_resource(gap_bag(&seed));
+}
+
+/// Replaces keyboard input with the recorded keystrokes that are due.
+fn feed_keystrokes(
+    mut keyboard_events: ResMut<Messages<KeyboardInput>>,
+    mut playback: ResMut<Playback>,
+    recording: Res<RunRecording>,
+    window: Query<Entity, With<PrimaryWindow>>,
+) {
+    keyboard_events.clear();
+
+    let window = window.single().unwrap_or(Entity::PLACEHOLDER);
+
+    while let Some(&(time, char)) = playback.replay.keystrokes.get(playback.cursor) {
+        if time > recording.elapsed {
+            break;
+        }
+
+        keyboard_events.write(KeyboardInput {
+            key_code: KeyCode::Unidentified(NativeKeyCode::Unidentified),
+            logical_key: Key::Character(char.to_string().into()),
+            state: ButtonState::Pressed,
+            text: Some(char.to_string().into()),
+            repeat: false,
+            window,
+        });
+
+        playback.cursor += 1;
+    }
+}
+
+fn check_playback(playback: Res<Playback>, score: Res<Score>) {
+    if score.0 == playback.replay.score {
+        info!("Replay finished with the recorded score of {}", score.0);
+    } else {
+        warn!(
+            "Replay diverged: scored {} but the recording scored {}",
+            score.0, playback.replay.score
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::{Difficulty, DifficultyPresets};
+
+    #[test]
+    fn round_trips_through_text() {
+        let replay = Replay {
+            seed: 1234,
+            score: 56,
+            timestep: TIMESTEP,
+            difficulty: DifficultyPresets::default().get(Difficulty::Hard).clone(),
+            adaptive: true,
+            weather: true,
+            typing_mode: TypingMode::Strict,
+            keystrokes: vec![(0.532, 'a'), (1.25, ' '), (2., 'z')],
+        };
+
+        assert_eq!(Replay::from_text(&replay.to_text()).unwrap(), replay);
+    }
+
+    #[test]
+    fn reads_version_1_speed_and_spacing() {
+        let text = "typey_birb replay 1\n\
+                    seed 42\n\
+                    score 3\n\
+                    timestep 0.016666668\n\
+                    speed 2 4\n\
+                    spacing 12\n\
+                    key 0.5 s\n";
+
+        let replay = Replay::from_text(text).unwrap();
+
+        assert_eq!(replay.seed, 42);
+        assert_eq!(replay.score, 3);
+        assert!(!replay.adaptive);
+        assert!(!replay.weather);
+        assert_eq!(replay.typing_mode, TypingMode::Forgiving);
+        assert_eq!(replay.keystrokes, vec![(0.5, 's')]);
+        assert_eq!(
+            replay.difficulty,
+            DifficultyPreset {
+                start_speed: 2.,
+                max_speed: 4.,
+                acceleration: Acceleration::Linear,
+                obstacles_to_max_speed: 20,
+                start_spacing: 12.,
+                end_spacing: 12.,
+                obstacles_to_end_spacing: 0,
+                gap_variance: 1.,
+            }
+        );
+    }
+
+    #[test]
+    fn round_trips_each_typing_mode() {
+        for typing_mode in [TypingMode::Forgiving, TypingMode::Strict] {
+            let replay = Replay {
+                seed: 7,
+                score: 0,
+                timestep: TIMESTEP,
+                difficulty: DifficultyPresets::default().get(Difficulty::Normal).clone(),
+                adaptive: false,
+                weather: false,
+                typing_mode,
+                keystrokes: vec![],
+            };
+
+            assert_eq!(
+                Replay::from_text(&replay.to_text()).unwrap().typing_mode,
+                typing_mode
+            );
+        }
+    }
+
+    #[test]
+    fn reads_version_3_as_forgiving() {
+        let preset = DifficultyPresets::default().get(Difficulty::Normal).clone();
+        let text = format!(
+            "typey_birb replay 3\n\
+             seed 42\n\
+             score 3\n\
+             timestep 0.016666668\n\
+             difficulty {}\n\
+             adaptive false\n\
+             weather true\n",
+            ron::to_string(&preset).unwrap()
+        );
+
+        let replay = Replay::from_text(&text).unwrap();
+
+        assert_eq!(replay.difficulty, preset);
+        assert_eq!(replay.typing_mode, TypingMode::Forgiving);
+    }
+
+    #[test]
+    fn rejects_an_unknown_typing_mode() {
+        let text = "typey_birb replay 4\ntyping_mode sloppy\n";
+
+        assert!(matches!(
+            Replay::from_text(text),
+            Err(ReplayError::Malformed { line: 2 })
+        ));
+    }
+
+    #[test]
+    fn rejects_version_1_without_speed() {
+        let text = "typey_birb replay 1\nseed 42\nscore 3\ntimestep 0.016666668\nspacing 12\n";
+
+        assert!(matches!(
+            Replay::from_text(text),
+            Err(ReplayError::Missing("speed"))
+        ));
+    }
+}
@@ -0,0 +1,485 @@
+use bevy::{
+    math::{
+        bounding::{Aabb3d, BoundingVolume},
+        Vec3A, Vec3Swizzles,
+    },
+    prelude::*,
+};
+use rand::prelude::*;
+
+use crate::{
+    audio::sound_effect,
+    collider::{BirbCollider, CylinderCollider},
+    loading::{AudioAssets, GltfAssets},
+    luck::Seed,
+    movement, obstacle_movement, AppState, CurrentRotationZ, GapPass, HitBox, InRun, Obstacle,
+    ObstacleCollider, ObstacleParts, ScoreCollider, Scrolling, Speed, TargetPosition, BIRB_MAX_Y,
+    BIRB_MIN_Y, GRAVITY, GROUND_Y,
+};
+
+/// Where the rival likes to fly, relative to the player.
+const RIVAL_X: f32 = 3.;
+const RIVAL_START_Y: f32 = 4.;
+/// How far the rival is willing to stray from the middle of the next gap.
+const GAP_TOLERANCE: f32 = 0.125;
+/// The rival never circles closer than this when gloating around an obstacle.
+const MIN_ORBIT_DISTANCE: f32 = 2.5;
+
+pub struct RivalPlugin;
+
+#[derive(Component)]
+pub struct Rival;
+
+/// Marks a rival that flew into an obstacle and is now out of the race.
+#[derive(Component)]
+pub struct Crashed {
+    velocity_y: f32,
+}
+
+/// How well a rival types.
+#[derive(Clone, Debug)]
+pub struct RivalSkill {
+    pub wpm: f32,
+    /// The chance that any given keystroke is a typo.
+    pub error_rate: f32,
+}
+impl RivalSkill {
+    fn keystroke_interval(&self) -> f32 {
+        // By convention, a "word" is five keystrokes.
+        60. / (self.wpm * 5.)
+    }
+}
+
+pub struct RivalProfile {
+    pub name: &'static str,
+    pub skill: RivalSkill,
+    /// Said on the start screen before the race.
+    pub greeting: &'static str,
+    /// Said on the end screen when the player crashes first.
+    pub taunt: &'static str,
+    /// Said on the end screen when the rival crashes first.
+    pub concession: &'static str,
+}
+
+/// The rivals standing between the player and the flock, in the order they must be beaten.
+pub const RIVALS: [RivalProfile; 4] = [
+    RivalProfile {
+        name: "Goldie",
+        skill: RivalSkill {
+            wpm: 30.,
+            error_rate: 0.08,
+        },
+        greeting: concat!(
+            "So you want to join the flock, eh?\n",
+            "You'll have to beat me first!"
+        ),
+        taunt: concat!(
+            "Oh wow, ouch!\n",
+            "It's a shame you can't move side to side,\n",
+            "the path is a bit clearer over here!"
+        ),
+        concession: concat!(
+            "Not bad, kid!\n",
+            "There may be room for you in the flock\n",
+            "as an unpaid apprentice."
+        ),
+    },
+    RivalProfile {
+        name: "Rusty",
+        skill: RivalSkill {
+            wpm: 45.,
+            error_rate: 0.06,
+        },
+        greeting: concat!(
+            "Goldie let you through? Figures.\n",
+            "I'm not so easily impressed."
+        ),
+        taunt: concat!(
+            "Ha! Too slow, apprentice.\n",
+            "Come back when your fingers\n",
+            "can keep up with your beak."
+        ),
+        concession: concat!(
+            "That was a close one!\n",
+            "With moves like that, you'll\n",
+            "fit in well here!"
+        ),
+    },
+    RivalProfile {
+        name: "Cobalt",
+        skill: RivalSkill {
+            wpm: 60.,
+            error_rate: 0.04,
+        },
+        greeting: concat!("I am Cobalt, wing commander.\n", "Nobody gets past me."),
+        taunt: concat!(
+            "Sloppy. Very sloppy.\n",
+            "The flock needs precision,\n",
+            "not enthusiasm."
+        ),
+        concession: concat!(
+            "Impossible...\n",
+            "Very well. Only the flock leader\n",
+            "stands in your way now."
+        ),
+    },
+    RivalProfile {
+        name: "Onyx",
+        skill: RivalSkill {
+            wpm: 80.,
+            error_rate: 0.02,
+        },
+        greeting: concat!("So you're the one everybirb\n", "keeps chirping about."),
+        taunt: concat!(
+            "The sky isn't ready for you yet.\n",
+            "But I'll be waiting when it is."
+        ),
+        concession: concat!(
+            "I... wha... wow!\n",
+            "What am I even doing with my life?\n",
+            "The flock is yours, if you'll have us!"
+        ),
+    },
+];
+
+/// How many rivals the player has beaten. Persisted in prefs.
+#[derive(Resource, Reflect, Clone, Default)]
+pub struct RivalProgress {
+    defeated: usize,
+}
+impl RivalProgress {
+    /// The index of the next rival to beat, or the final rival once they've all been beaten.
+    pub fn next_rival(&self) -> usize {
+        self.defeated.min(RIVALS.len() - 1)
+    }
+}
+
+/// The index into [`RIVALS`] of the rival in the current race.
+#[derive(Resource, Default)]
+pub struct CurrentRival(pub usize);
+impl CurrentRival {
+    pub fn profile(&self) -> &'static RivalProfile {
+        &RIVALS[self.0]
+    }
+}
+
+/// The points the rival has scored this run. Gaps are judged just like the player's, so a rival
+/// that types quickly and accurately scores more by flying through the middle of them.
+#[derive(Resource, Default)]
+pub struct RivalScore(pub u32);
+impl RivalScore {
+    /// How many more points the rival has than the player. Negative when the rival is behind.
+    pub fn lead(&self, score: u32) -> i64 {
+        self.0 as i64 - score as i64
+    }
+}
+
+#[derive(Component)]
+struct RivalTypist {
+    timer: Timer,
+    error_rate: f32,
+    rng: StdRng,
+}
+
+#[derive(Component)]
+struct Orbit {
+    angle: f32,
+    distance: f32,
+    origin: Vec2,
+}
+
+impl Plugin for RivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentRival>()
+            .init_resource::<RivalScore>();
+
+        app.add_systems(OnExit(AppState::StartScreen), spawn_rival);
+        app.add_systems(OnEnter(AppState::EndScreen), record_victory);
+        app.add_systems(
+            Update,
+            rival_scoring
+                .after(rival_approach)
+                .after(obstacle_movement)
+                .run_if(in_state(AppState::Playing)),
+        );
+        // The rival keeps racing while the birb crashes, or tumbles down if it crashed first.
+        app.add_systems(
+            Update,
+            (
+                (rival_approach, rival_typing).before(movement),
+                rival_collision.after(movement),
+                crashed_rival_movement,
+            )
+                .run_if(in_state(Scrolling)),
+        );
+        app.add_systems(
+            Update,
+            rival_movement_end_screen.run_if(in_state(AppState::EndScreen)),
+        );
+    }
+}
+
+fn spawn_rival(
+    mut commands: Commands,
+    gltf_assets: Res<GltfAssets>,
+    progress: Res<RivalProgress>,
+    mut current: ResMut<CurrentRival>,
+    seed: Res<Seed>,
+) {
+    current.0 = progress.next_rival();
+    let skill = &current.profile().skill;
+
+    let pos = Vec3::new(-10., RIVAL_START_Y, 0.);
+
+    commands.spawn((
+        SceneRoot(gltf_assets.birb_gold.clone()),
+        Transform::from_translation(pos).with_scale(Vec3::splat(0.25)),
+        TargetPosition(pos),
+        CurrentRotationZ(0.),
+        HitBox(Aabb3d::new(Vec3A::splat(0.), Vec3A::new(0.2, 0.3, 0.25))),
+        BirbCollider::BIRB,
+        RivalTypist {
+            timer: Timer::from_seconds(skill.keystroke_interval(), TimerMode::Repeating),
+            error_rate: skill.error_rate,
+            rng: StdRng::seed_from_u64(seed.0),
+        },
+        Rival,
+        Name::new("Rival"),
+        DespawnOnExit(InRun),
+    ));
+}
+
+/// Swoops in from behind the player and settles at [`RIVAL_X`].
+fn rival_approach(
+    mut query: Query<(&mut Transform, &mut TargetPosition), With<Rival>>,
+    time: Res<Time>,
+) {
+    let speed = 5.;
+
+    for (mut transform, mut target) in query.iter_mut() {
+        if transform.translation.x < RIVAL_X {
+            transform.translation.x =
+                (transform.translation.x + speed * time.delta_secs()).min(RIVAL_X);
+        }
+
+        // Only the rival's height is up to its typing.
+        target.0.x = transform.translation.x;
+        target.0.z = transform.translation.z;
+    }
+}
+
+/// Types at the rival's pace, flapping towards the middle of the next gap.
+fn rival_typing(
+    mut query: Query<(&Transform, &mut TargetPosition, &mut RivalTypist), With<Rival>>,
+    score_collider_query: Query<&GlobalTransform, With<ScoreCollider>>,
+    time: Res<Time>,
+) {
+    for (transform, mut target, mut typist) in query.iter_mut() {
+        typist.timer.tick(time.delta());
+
+        // The middle of the nearest gap that the rival hasn't flown past yet.
+        let next_gap_y = score_collider_query
+            .iter()
+            .map(|t| t.translation())
+            .filter(|t| t.x > transform.translation.x - 0.5)
+            .min_by(|a, b| a.x.partial_cmp(&b.x).unwrap())
+            .map(|t| t.y)
+            .unwrap_or(RIVAL_START_Y);
+
+        for _ in 0..typist.timer.times_finished_this_tick() {
+            let diff = next_gap_y - target.0.y;
+            if diff.abs() <= GAP_TOLERANCE {
+                break;
+            }
+
+            let error_rate = typist.error_rate;
+            if typist.rng.random::<f32>() < error_rate {
+                continue;
+            }
+
+            target.0.y = (target.0.y + 0.25 * diff.signum()).clamp(BIRB_MIN_Y, BIRB_MAX_Y);
+        }
+    }
+}
+
+fn rival_collision(
+    mut commands: Commands,
+    rival_query: Query<(Entity, &BirbCollider, &Transform, &CurrentRotationZ), With<Rival>>,
+    obstacle_collider_query: Query<
+        (&HitBox, &CylinderCollider, &GlobalTransform),
+        With<ObstacleCollider>,
+    >,
+    audio_assets: Res<AudioAssets>,
+) {
+    for (entity, rival_collider, transform, rotation) in rival_query.iter() {
+        let rival_spheres = rival_collider.spheres(transform.translation, rotation.0);
+
+        let hit_obstacle =
+            obstacle_collider_query
+                .iter()
+                .any(|(obstacle_aabb, collider, transform)| {
+                    let center = transform.translation();
+                    let obstacle_aabb = obstacle_aabb.0.translated_by(center);
+
+                    collider
+                        .min_clearance(center, &obstacle_aabb, &rival_spheres, 0.)
+                        .is_some_and(|clearance| clearance < 0.)
+                });
+
+        if !hit_obstacle {
+            continue;
+        }
+
+        commands
+            .entity(entity)
+            .remove::<(TargetPosition, HitBox, BirbCollider, RivalTypist)>()
+            .insert(Crashed { velocity_y: 0. });
+
+        commands.spawn(sound_effect(audio_assets.crash.clone()));
+    }
+}
+
+/// Unlocks the next rival when the current one crashes before the player does.
+fn record_victory(
+    crashed_rival_query: Query<(), (With<Rival>, With<Crashed>)>,
+    current: Res<CurrentRival>,
+    mut progress: ResMut<RivalProgress>,
+) {
+    if crashed_rival_query.is_empty() {
+        return;
+    }
+
+    progress.defeated = progress.defeated.max(current.0 + 1);
+}
+
+/// Tumbles a crashed rival to the ground, where it's left behind as the world scrolls by.
+fn crashed_rival_movement(
+    mut query: Query<(&mut Transform, &mut Crashed), With<Rival>>,
+    time: Res<Time>,
+    speed: Res<Speed>,
+) {
+    for (mut transform, mut crashed) in query.iter_mut() {
+        transform.translation.x -= time.delta_secs() * speed.current;
+
+        if transform.translation.y <= GROUND_Y {
+            continue;
+        }
+
+        crashed.velocity_y -= GRAVITY * time.delta_secs();
+        transform.translation.y =
+            (transform.translation.y + crashed.velocity_y * time.delta_secs()).max(GROUND_Y);
+        transform.rotate_z(time.delta_secs() * 6.);
+    }
+}
+
+/// Scores the gaps that the rival flies through. A crashed rival stops scoring.
+fn rival_scoring(
+    rival_query: Query<
+        (&BirbCollider, &Transform, &CurrentRotationZ),
+        (With<Rival>, Without<Crashed>),
+    >,
+    obstacle_query: Query<(&Transform, &ObstacleParts), With<Obstacle>>,
+    gap_query: Query<&Transform, With<ScoreCollider>>,
+    mut rival_score: ResMut<RivalScore>,
+    speed: Res<Speed>,
+    time: Res<Time>,
+) {
+    let Ok((collider, transform, rotation)) = rival_query.single() else {
+        return;
+    };
+
+    let delta = time.delta_secs() * speed.current;
+    let x = transform.translation.x;
+    let spheres = collider.spheres(transform.translation, rotation.0);
+
+    // The obstacles that scrolled past the rival this frame.
+    for (obstacle, parts) in &obstacle_query {
+        if obstacle.translation.x >= x || obstacle.translation.x + delta < x {
+            continue;
+        }
+
+        let Ok(gap) = gap_query.get(parts.gap) else {
+            continue;
+        };
+
+        rival_score.0 += GapPass::judge(&spheres, gap.translation.y).points();
+    }
+}
+
+fn rival_movement_end_screen(
+    mut commands: Commands,
+    mut query: Query<
+        (Entity, &mut Transform, Option<&mut Orbit>),
+        (With<Rival>, Without<Obstacle>, Without<Crashed>),
+    >,
+    obstacle_query: Query<&Transform, With<Obstacle>>,
+    time: Res<Time>,
+) {
+    let Ok((entity, mut transform, maybe_orbit)) = query.single_mut() else {
+        return;
+    };
+
+    let Some(mut orbit) = maybe_orbit else {
+        let Some(closest_obstacle) = obstacle_query
+            .iter()
+            .map(|t| {
+                (
+                    t.translation
+                        .xz()
+                        .distance_squared(transform.translation.xz()),
+                    t.translation.xz(),
+                )
+            })
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+        else {
+            return;
+        };
+
+        commands.entity(entity).insert(Orbit {
+            angle: closest_obstacle.1.angle_to(transform.translation.xz()),
+            origin: closest_obstacle.1,
+            distance: closest_obstacle.0.sqrt(),
+        });
+        return;
+    };
+
+    // The rival races in the same lane as the obstacles, so it may be right on top of one.
+    // Drift outwards to a comfortable distance while circling.
+    if orbit.distance < MIN_ORBIT_DISTANCE {
+        orbit.distance = orbit
+            .distance
+            .lerp(MIN_ORBIT_DISTANCE, time.delta_secs())
+            .min(MIN_ORBIT_DISTANCE);
+    }
+
+    // Convert speed to angular speed.
+    let speed = 5. / std::f32::consts::TAU / orbit.distance.max(MIN_ORBIT_DISTANCE) * 2.;
+    let step = speed * time.delta_secs();
+
+    orbit.angle += step;
+
+    let (sin, cos) = orbit.angle.sin_cos();
+
+    transform.translation.x = sin * orbit.distance + orbit.origin.x;
+    transform.translation.z = cos * orbit.distance + orbit.origin.y;
+
+    // face direction of movement
+    let y_rot = transform.rotation.to_euler(EulerRot::XYZ).1;
+    let diff = orbit.angle - y_rot;
+
+    let next_y_rot = if diff.abs() < step {
+        y_rot + step * diff.signum()
+    } else {
+        orbit.angle
+    };
+
+    transform.rotation = Quat::from_euler(
+        EulerRot::XYZ,
+        0.,
+        next_y_rot,
+        time.elapsed_secs().cos() / 4.,
+    );
+
+    let floaty = time.elapsed_secs().sin();
+    transform.translation.y = 4. + floaty;
+}
@@ -0,0 +1,202 @@
+//! Trees, rocks, fences and clouds scattered across each ground chunk, away from the flight lane.
+//!
+//! Every piece of scenery of a kind shares the same mesh and material, so they're drawn as
+//! instances of each other.
+
+use std::ops::Range;
+
+use bevy::{light::NotShadowCaster, prelude::*};
+use rand::prelude::*;
+
+use crate::ground::Terrain;
+
+const TREES: usize = 28;
+const ROCKS: usize = 14;
+const CLOUDS: usize = 5;
+/// The chance of each chunk having a fence running along the foreground.
+const FENCE_CHANCE: f64 = 0.6;
+const FENCE_SEGMENT_LENGTH: f32 = 2.;
+
+/// Trees stay behind the flight lane, where they can't hide the birb.
+const TREE_Z: Range<f32> = -18.0..-4.;
+/// Rocks are small enough to sit in front of the flight lane, too.
+const ROCK_Z: [Range<f32>; 2] = [-18.0..-3.5, 3.5..7.];
+const FENCE_Z: f32 = 4.5;
+const CLOUD_Y: Range<f32> = 9.0..13.;
+const CLOUD_Z: Range<f32> = -25.0..-8.;
+
+#[derive(Resource)]
+pub struct SceneryAssets {
+    trunk: Handle<Mesh>,
+    foliage: Handle<Mesh>,
+    rock: Handle<Mesh>,
+    cloud: Handle<Mesh>,
+    post: Handle<Mesh>,
+    rail: Handle<Mesh>,
+    wood: Handle<StandardMaterial>,
+    stone: Handle<StandardMaterial>,
+    cloud_material: Handle<StandardMaterial>,
+}
+impl FromWorld for SceneryAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let trunk = meshes.add(Cylinder::new(0.15, 1.));
+        let foliage = meshes.add(Cone::new(0.8, 2.).mesh().resolution(8));
+        let rock = meshes.add(Sphere::new(0.5).mesh().ico(1).unwrap());
+        let cloud = meshes.add(Sphere::new(1.).mesh().ico(2).unwrap());
+        let post = meshes.add(Cuboid::new(0.1, 0.8, 0.1));
+        let rail = meshes.add(Cuboid::new(FENCE_SEGMENT_LENGTH, 0.08, 0.05));
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let wood = materials.add(Color::srgb(0.45, 0.3, 0.18));
+        let stone = materials.add(StandardMaterial {
+            base_color: Color::srgb(0.55, 0.55, 0.58),
+            perceptual_roughness: 1.,
+            ..default()
+        });
+        let cloud_material = materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            ..default()
+        });
+
+        Self {
+            trunk,
+            foliage,
+            rock,
+            cloud,
+            post,
+            rail,
+            wood,
+            stone,
+            cloud_material,
+        }
+    }
+}
+
+/// Scatters scenery across the ground chunk that's about to be built from `terrain`.
+///
+/// `foliage` is shared by all of the chunk's trees, so that they take on the colors of the biome
+/// they grew in.
+pub fn scatter(
+    parent: &mut ChildSpawnerCommands,
+    terrain: &Terrain,
+    rng: &mut impl Rng,
+    foliage: Handle<StandardMaterial>,
+    assets: &SceneryAssets,
+    length: f32,
+) {
+    let x_range = -length / 2.0..length / 2.;
+
+    for _ in 0..TREES {
+        let x = rng.random_range(x_range.clone());
+        let z = rng.random_range(TREE_Z);
+        let scale = rng.random_range(0.7..1.4);
+
+        parent
+            .spawn((
+                Transform::from_xyz(x, terrain.chunk_height(x, z), z)
+                    .with_scale(Vec3::splat(scale)),
+                Visibility::default(),
+                Name::new("Tree"),
+            ))
+            .with_children(|tree| {
+                tree.spawn((
+                    Mesh3d(assets.trunk.clone()),
+                    MeshMaterial3d(assets.wood.clone()),
+                    Transform::from_xyz(0., 0.5, 0.),
+                ));
+                tree.spawn((
+                    Mesh3d(assets.foliage.clone()),
+                    MeshMaterial3d(foliage.clone()),
+                    Transform::from_xyz(0., 1.8, 0.),
+                ));
+            });
+    }
+
+    for _ in 0..ROCKS {
+        let x = rng.random_range(x_range.clone());
+        let z_range = ROCK_Z.choose(rng).unwrap().clone();
+        let z = rng.random_range(z_range);
+        let scale = Vec3::new(
+            rng.random_range(0.4..1.),
+            rng.random_range(0.3..0.6),
+            rng.random_range(0.4..1.),
+        );
+
+        parent.spawn((
+            Mesh3d(assets.rock.clone()),
+            MeshMaterial3d(assets.stone.clone()),
+            // Half sunk into the ground.
+            Transform::from_xyz(x, terrain.chunk_height(x, z), z)
+                .with_rotation(Quat::from_rotation_y(
+                    rng.random_range(0.0..std::f32::consts::TAU),
+                ))
+                .with_scale(scale),
+            Name::new("Rock"),
+        ));
+    }
+
+    if rng.random_bool(FENCE_CHANCE) {
+        let segments = rng.random_range(3..8);
+        let start =
+            rng.random_range(x_range.start..x_range.end - segments as f32 * FENCE_SEGMENT_LENGTH);
+
+        for i in 0..=segments {
+            let x = start + i as f32 * FENCE_SEGMENT_LENGTH;
+            let y = terrain.chunk_height(x, FENCE_Z);
+
+            parent.spawn((
+                Mesh3d(assets.post.clone()),
+                MeshMaterial3d(assets.wood.clone()),
+                Transform::from_xyz(x, y + 0.3, FENCE_Z),
+                Name::new("FencePost"),
+            ));
+
+            if i == segments {
+                break;
+            }
+
+            let mid_x = x + FENCE_SEGMENT_LENGTH / 2.;
+            let mid_y = terrain.chunk_height(mid_x, FENCE_Z);
+            for rail_y in [0.3, 0.55] {
+                parent.spawn((
+                    Mesh3d(assets.rail.clone()),
+                    MeshMaterial3d(assets.wood.clone()),
+                    Transform::from_xyz(mid_x, mid_y + rail_y, FENCE_Z),
+                    Name::new("FenceRail"),
+                ));
+            }
+        }
+    }
+
+    for _ in 0..CLOUDS {
+        let center = Vec3::new(
+            rng.random_range(x_range.clone()),
+            rng.random_range(CLOUD_Y),
+            rng.random_range(CLOUD_Z),
+        );
+
+        parent
+            .spawn((
+                Transform::from_translation(center),
+                Visibility::default(),
+                Name::new("Cloud"),
+            ))
+            .with_children(|cloud| {
+                for _ in 0..rng.random_range(3..6) {
+                    cloud.spawn((
+                        Mesh3d(assets.cloud.clone()),
+                        MeshMaterial3d(assets.cloud_material.clone()),
+                        Transform::from_xyz(
+                            rng.random_range(-1.5..1.5),
+                            rng.random_range(-0.3..0.3),
+                            rng.random_range(-0.5..0.5),
+                        )
+                        .with_scale(Vec3::splat(rng.random_range(0.7..1.3))),
+                        NotShadowCaster,
+                    ));
+                }
+            });
+    }
+}
@@ -0,0 +1,371 @@
+use bevy::{
+    color::palettes::css::LIME,
+    prelude::*,
+    window::{MonitorSelection, PrimaryWindow, WindowMode},
+};
+
+use crate::{
+    adaptive::AdaptiveDifficulty,
+    audio::{KeySounds, MusicVolume, Muted, SfxVolume, WordChime, MAX_VOLUME},
+    camera::ReducedMotion,
+    difficulty::Difficulty,
+    typing::{Suspended, TypingTarget},
+    ui::FONT_SIZE,
+    weather::WeatherEvents,
+    Action, FontAssets, StartMenu,
+};
+
+pub struct SettingsPlugin;
+
+/// How typos are treated while playing.
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TypingMode {
+    /// Typos are ignored.
+    #[default]
+    Forgiving,
+    /// Typos send the in-game words back to their start. Menu words are left alone.
+    Strict,
+}
+
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FontSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+impl FontSize {
+    fn ui_scale(&self) -> f32 {
+        match self {
+            FontSize::Small => 0.85,
+            FontSize::Medium => 1.,
+            FontSize::Large => 1.2,
+        }
+    }
+}
+
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fullscreen(pub bool);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setting {
+    MusicVolume,
+    SfxVolume,
+    Mute,
+    KeySounds,
+    WordChime,
+    Difficulty,
+    Adaptive,
+    TypingMode,
+    FontSize,
+    Fullscreen,
+    Weather,
+    Motion,
+}
+impl Setting {
+    const ALL: [Setting; 12] = [
+        Setting::MusicVolume,
+        Setting::SfxVolume,
+        Setting::Mute,
+        Setting::KeySounds,
+        Setting::WordChime,
+        Setting::Difficulty,
+        Setting::Adaptive,
+        Setting::TypingMode,
+        Setting::FontSize,
+        Setting::Fullscreen,
+        Setting::Weather,
+        Setting::Motion,
+    ];
+
+    /// The word to type to change this setting.
+    fn word(&self) -> &'static str {
+        match self {
+            Setting::MusicVolume => "music",
+            Setting::SfxVolume => "effects",
+            Setting::Mute => "mute",
+            Setting::KeySounds => "keys",
+            Setting::WordChime => "chime",
+            Setting::Difficulty => "difficulty",
+            Setting::Adaptive => "adaptive",
+            Setting::TypingMode => "mode",
+            Setting::FontSize => "font",
+            Setting::Fullscreen => "fullscreen",
+            Setting::Weather => "weather",
+            Setting::Motion => "motion",
+        }
+    }
+}
+
+#[derive(Component)]
+struct SettingValue(Setting);
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(StartMenu::Settings),
+            (suspend_words, settings_screen).chain(),
+        );
+        app.add_systems(OnExit(StartMenu::Settings), resume_words);
+        app.add_systems(
+            Update,
+            (change_settings, back).run_if(in_state(StartMenu::Settings)),
+        );
+        app.add_systems(Update, open_settings.run_if(in_state(StartMenu::Main)));
+        app.add_systems(
+            Update,
+            (
+                update_setting_values,
+                apply_font_size.run_if(resource_changed::<FontSize>),
+                apply_fullscreen.run_if(resource_changed::<Fullscreen>),
+            ),
+        );
+    }
+}
+
+fn settings_screen(mut commands: Commands, font_assets: Res<FontAssets>) {
+    let text_font = TextFont {
+        font: font_assets.main.clone(),
+        font_size: FONT_SIZE,
+        ..default()
+    };
+
+    let container = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Name::new("SettingsScreen"),
+            DespawnOnExit(StartMenu::Settings),
+        ))
+        .id();
+
+    let bg = commands
+        .spawn((
+            Node {
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                ..default()
+            },
+            BackgroundColor(Color::BLACK),
+        ))
+        .id();
+
+    let title = commands
+        .spawn((
+            Text::new("SETTINGS"),
+            text_font.clone(),
+            TextColor(Color::WHITE),
+        ))
+        .id();
+
+    commands.entity(container).add_children(&[bg]);
+    commands.entity(bg).add_children(&[title]);
+
+    for setting in Setting::ALL {
+        let word = setting.word();
+
+        let row = commands
+            .spawn((
+                Text::default(),
+                text_font.clone(),
+                TextColor(LIME.into()),
+                TypingTarget::new_whole(word.into(), vec![Action::ChangeSetting(setting)]),
+            ))
+            .with_child((
+                TextSpan::new(word.to_uppercase()),
+                text_font.clone(),
+                TextColor(Color::srgb_u8(255, 235, 146)),
+            ))
+            .with_child((
+                TextSpan::default(),
+                text_font.clone(),
+                TextColor(Color::WHITE),
+                SettingValue(setting),
+            ))
+            .id();
+
+        commands.entity(bg).add_child(row);
+    }
+
+    let back = commands
+        .spawn((
+            Text::default(),
+            text_font.clone(),
+            TextColor(LIME.into()),
+            TypingTarget::new_whole("back".into(), vec![Action::Back]),
+        ))
+        .with_child((
+            TextSpan::new("BACK"),
+            text_font.clone(),
+            TextColor(Color::srgb_u8(255, 235, 146)),
+        ))
+        .id();
+
+    commands.entity(bg).add_child(back);
+}
+
+/// Keep the words in the top and bottom bars from reacting while settings are being typed.
+fn suspend_words(mut commands: Commands, query: Query<Entity, With<TypingTarget>>) {
+    for entity in &query {
+        commands.entity(entity).insert(Suspended);
+    }
+}
+
+fn resume_words(mut commands: Commands, query: Query<Entity, With<Suspended>>) {
+    for entity in &query {
+        commands.entity(entity).remove::<Suspended>();
+    }
+}
+
+fn open_settings(mut events: MessageReader<Action>, mut next_state: ResMut<NextState<StartMenu>>) {
+    for e in events.read() {
+        if let Action::Settings = e {
+            next_state.set(StartMenu::Settings);
+        }
+    }
+}
+
+fn back(mut events: MessageReader<Action>, mut next_state: ResMut<NextState<StartMenu>>) {
+    for e in events.read() {
+        if let Action::Back = e {
+            next_state.set(StartMenu::Main);
+        }
+    }
+}
+
+/// Cycles a setting through its values each time its word is typed.
+fn change_settings(
+    mut events: MessageReader<Action>,
+    mut music_volume: ResMut<MusicVolume>,
+    mut sfx_volume: ResMut<SfxVolume>,
+    mut muted: ResMut<Muted>,
+    mut key_sounds: ResMut<KeySounds>,
+    mut word_chime: ResMut<WordChime>,
+    mut difficulty: ResMut<Difficulty>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+    mut typing_mode: ResMut<TypingMode>,
+    mut font_size: ResMut<FontSize>,
+    mut fullscreen: ResMut<Fullscreen>,
+    mut weather: ResMut<WeatherEvents>,
+    mut reduced_motion: ResMut<ReducedMotion>,
+) {
+    for e in events.read() {
+        let Action::ChangeSetting(setting) = e else {
+            continue;
+        };
+
+        match setting {
+            Setting::MusicVolume => music_volume.0 = (music_volume.0 + 1) % (MAX_VOLUME + 1),
+            Setting::SfxVolume => sfx_volume.0 = (sfx_volume.0 + 1) % (MAX_VOLUME + 1),
+            Setting::Mute => {
+                // Cycles through nothing, music, effects and everything.
+                *muted = Muted {
+                    music: !muted.music,
+                    effects: muted.effects != muted.music,
+                }
+            }
+            Setting::KeySounds => {
+                *key_sounds = match *key_sounds {
+                    KeySounds::Plain => KeySounds::Varied,
+                    KeySounds::Varied => KeySounds::Clicks,
+                    KeySounds::Clicks => KeySounds::Plain,
+                }
+            }
+            Setting::WordChime => word_chime.0 = !word_chime.0,
+            Setting::Difficulty => {
+                *difficulty = match *difficulty {
+                    Difficulty::Easy => Difficulty::Normal,
+                    Difficulty::Normal => Difficulty::Hard,
+                    Difficulty::Hard => Difficulty::Custom,
+                    Difficulty::Custom => Difficulty::Easy,
+                }
+            }
+            Setting::Adaptive => adaptive.0 = !adaptive.0,
+            Setting::TypingMode => {
+                *typing_mode = match *typing_mode {
+                    TypingMode::Forgiving => TypingMode::Strict,
+                    TypingMode::Strict => TypingMode::Forgiving,
+                }
+            }
+            Setting::FontSize => {
+                *font_size = match *font_size {
+                    FontSize::Small => FontSize::Medium,
+                    FontSize::Medium => FontSize::Large,
+                    FontSize::Large => FontSize::Small,
+                }
+            }
+            Setting::Fullscreen => fullscreen.0 = !fullscreen.0,
+            Setting::Weather => weather.0 = !weather.0,
+            Setting::Motion => reduced_motion.0 = !reduced_motion.0,
+        }
+    }
+}
+
+fn update_setting_values(
+    mut query: Query<(&mut TextSpan, &SettingValue)>,
+    music_volume: Res<MusicVolume>,
+    sfx_volume: Res<SfxVolume>,
+    muted: Res<Muted>,
+    key_sounds: Res<KeySounds>,
+    word_chime: Res<WordChime>,
+    difficulty: Res<Difficulty>,
+    adaptive: Res<AdaptiveDifficulty>,
+    typing_mode: Res<TypingMode>,
+    font_size: Res<FontSize>,
+    fullscreen: Res<Fullscreen>,
+    weather: Res<WeatherEvents>,
+    reduced_motion: Res<ReducedMotion>,
+) {
+    for (mut text, value) in query.iter_mut() {
+        let value = match value.0 {
+            Setting::MusicVolume => format!("{}/{MAX_VOLUME}", music_volume.0),
+            Setting::SfxVolume => format!("{}/{MAX_VOLUME}", sfx_volume.0),
+            Setting::Mute => match (muted.music, muted.effects) {
+                (false, false) => "Nothing",
+                (true, false) => "Music",
+                (false, true) => "Effects",
+                (true, true) => "Everything",
+            }
+            .to_string(),
+            Setting::KeySounds => format!("{:?}", *key_sounds),
+            Setting::WordChime => if word_chime.0 { "On" } else { "Off" }.to_string(),
+            Setting::Difficulty => format!("{:?}", *difficulty),
+            Setting::Adaptive => if adaptive.0 { "On" } else { "Off" }.to_string(),
+            Setting::TypingMode => format!("{:?}", *typing_mode),
+            Setting::FontSize => format!("{:?}", *font_size),
+            Setting::Fullscreen => if fullscreen.0 { "On" } else { "Off" }.to_string(),
+            Setting::Weather => if weather.0 { "On" } else { "Off" }.to_string(),
+            Setting::Motion => if reduced_motion.0 { "Reduced" } else { "Full" }.to_string(),
+        };
+        let value = format!("  {}", value.to_uppercase());
+
+        // Avoid triggering change detection and relayout every frame.
+        if text.0 != value {
+            text.0 = value;
+        }
+    }
+}
+
+fn apply_font_size(mut ui_scale: ResMut<UiScale>, font_size: Res<FontSize>) {
+    ui_scale.0 = font_size.ui_scale();
+}
+
+fn apply_fullscreen(
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    fullscreen: Res<Fullscreen>,
+) {
+    for mut window in window_query.iter_mut() {
+        window.mode = if fullscreen.0 {
+            WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+        } else {
+            WindowMode::Windowed
+        };
+    }
+}
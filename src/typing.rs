@@ -5,27 +5,45 @@ use bevy::{
 };
 use rand::{prelude::*, rng};
 
+use crate::{settings::TypingMode, AppState};
+
 pub struct TypingPlugin;
 
+/// How typos are treated in the current run. Usually the mode from the settings, but a replay
+/// plays back in the mode it was recorded in.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct ActiveTypingMode(pub TypingMode);
+
 #[derive(Resource)]
 pub struct WordList {
     words: Vec<String>,
     index: usize,
+    rng: StdRng,
 }
 
 impl Default for WordList {
     fn default() -> Self {
+        Self::seeded(rng().random())
+    }
+}
+
+impl WordList {
+    /// Builds a word list whose order is entirely determined by `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
         let mut words = crate::words::WORDS
             .lines()
             .map(|w| w.to_owned())
             .filter(|w| w.chars().count() > 0)
             .collect::<Vec<_>>();
-        words.shuffle(&mut rng());
-        Self { words, index: 0 }
+        words.shuffle(&mut rng);
+        Self {
+            words,
+            index: 0,
+            rng,
+        }
     }
-}
 
-impl WordList {
     pub fn find_next_word(&mut self, not: &HashSet<char>) -> String {
         loop {
             let next = self.advance_word();
@@ -38,13 +56,23 @@ impl WordList {
     fn advance_word(&mut self) -> String {
         self.index += 1;
         if self.index >= self.words.len() {
-            self.words.shuffle(&mut rng());
+            self.words.shuffle(&mut self.rng);
             self.index = 0;
         }
         self.words[self.index].clone()
     }
 }
 
+/// Sent for every character typed, whether or not it matched a word.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct Keystroke {
+    pub correct: bool,
+}
+
+/// Typing targets with this component ignore the keyboard.
+#[derive(Component)]
+pub struct Suspended;
+
 #[derive(Component)]
 pub struct TypingTarget {
     pub letter_actions: Vec<crate::Action>,
@@ -77,6 +105,20 @@ impl TypingTarget {
         self.index += 1;
         self.current_char()
     }
+    /// Types the current character, sending any actions that result.
+    pub fn complete_char(&mut self, entity: Entity, events: &mut MessageWriter<crate::Action>) {
+        for action in self.letter_actions.iter() {
+            events.write(action.clone());
+        }
+
+        if self.advance_char().is_none() {
+            events.write(crate::Action::NewWord(entity));
+
+            for action in self.word_actions.iter() {
+                events.write(action.clone());
+            }
+        }
+    }
     pub fn replace(&mut self, new: String) {
         self.word = new;
         self.index = 0;
@@ -86,11 +128,18 @@ impl TypingTarget {
 impl Plugin for TypingPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<WordList>()
+            .init_resource::<ActiveTypingMode>()
+            .add_message::<Keystroke>()
+            .add_systems(OnExit(AppState::StartScreen), apply_typing_mode)
             .add_systems(Update, new_words)
             .add_systems(Update, keyboard);
     }
 }
 
+pub fn apply_typing_mode(mut commands: Commands, typing_mode: Res<TypingMode>) {
+    commands.insert_resource(ActiveTypingMode(*typing_mode));
+}
+
 fn new_words(
     mut events: MessageReader<crate::Action>,
     mut query: Query<(Entity, &mut TypingTarget)>,
@@ -116,8 +165,10 @@ fn new_words(
 
 fn keyboard(
     mut keyboard_events: MessageReader<KeyboardInput>,
-    mut query: Query<(Entity, &mut TypingTarget)>,
+    mut query: Query<(Entity, &mut TypingTarget), Without<Suspended>>,
     mut events: MessageWriter<crate::Action>,
+    mut keystrokes: MessageWriter<Keystroke>,
+    typing_mode: Res<ActiveTypingMode>,
 ) {
     for event in keyboard_events.read() {
         let mut ok = false;
@@ -134,28 +185,56 @@ fn keyboard(
             continue;
         };
 
+        let mut missed = vec![];
+        let mut completed = false;
+
         for (entity, mut target) in query.iter_mut() {
             let Some(next) = target.current_char() else {
                 continue;
             };
 
             if next != char {
+                missed.push(entity);
                 continue;
             }
 
-            for action in target.letter_actions.iter() {
-                events.write(action.clone());
-            }
+            target.complete_char(entity, &mut events);
+            completed |= target.current_char().is_none();
 
-            if target.advance_char().is_none() {
-                events.write(crate::Action::NewWord(entity));
+            ok = true;
+        }
 
-                for action in target.word_actions.iter() {
-                    events.write(action.clone());
+        // Menu words that lost out to another word start over, so that a half-typed word
+        // doesn't get in the way later.
+        if ok {
+            for entity in missed {
+                if let Ok((_, mut target)) = query.get_mut(entity) {
+                    if target.letter_actions.is_empty() && target.index > 0 {
+                        target.index = 0;
+                    }
                 }
             }
+        }
 
-            ok = true;
+        // So do menu words that were typed alongside a word that's now complete, like the "e" of
+        // "effects" at the end of "mute".
+        if completed {
+            for (_, mut target) in query.iter_mut() {
+                if target.letter_actions.is_empty() && target.current_char().is_some() {
+                    target.index = 0;
+                }
+            }
+        }
+
+        keystrokes.write(Keystroke { correct: ok });
+
+        // Typos only cost progress on the words that fly the birb, not on menu words.
+        if !ok && typing_mode.0 == TypingMode::Strict {
+            for (_, mut target) in query.iter_mut() {
+                if !target.letter_actions.is_empty() && target.index > 0 {
+                    target.index = 0;
+                }
+            }
         }
 
         if !ok {
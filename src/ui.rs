@@ -1,11 +1,17 @@
 use crate::{
+    adaptive::RunStats,
+    difficulty::ActiveDifficulty,
+    luck::Seed,
+    powerup::{ActivePowerUps, PowerUp},
+    rival::{Crashed, CurrentRival, Rival, RivalProgress, RivalScore, RIVALS},
     typing::{TypingTarget, WordList},
-    Action, AppState, FontAssets, GltfAssets, HighScore, Score,
+    Action, AppState, FontAssets, GltfAssets, HighScore, Score, StartMenu,
 };
 use bevy::{
     color::palettes::css::LIME, light::NotShadowCaster, platform::collections::HashSet, prelude::*,
     scene::SceneInstance,
 };
+use std::cmp::Ordering;
 
 pub struct UiPlugin;
 
@@ -15,33 +21,49 @@ struct ScoreText;
 #[derive(Component)]
 struct HighScoreText;
 #[derive(Component)]
+struct RivalLeadText;
+#[derive(Component)]
+struct PowerUpText;
+#[derive(Component)]
 struct RivalPortrait;
 #[derive(Component)]
 struct Decorated;
 
 pub const FONT_SIZE: f32 = 33.0;
+/// The words on the start screen's menu. Words in the bars never share a letter with them, so
+/// that typing a bar word can't get tangled up with the menu.
+const START_MENU_WORDS: [&str; 2] = ["start", "settings"];
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (update_score, update_high_score, update_targets));
+        app.add_systems(
+            Update,
+            (
+                update_score,
+                update_high_score,
+                update_rival_lead,
+                update_power_ups,
+                update_targets,
+            ),
+        );
         // Must run after SpawnScene schedule.
         app.add_systems(PostUpdate, decorate_rival_portrait);
 
         app.add_systems(OnExit(AppState::LoadingAssets), setup);
 
-        app.add_systems(OnEnter(AppState::StartScreen), start_screen);
+        app.add_systems(
+            OnEnter(AppState::StartScreen),
+            (start_portrait, reset_words),
+        );
+        app.add_systems(OnEnter(StartMenu::Main), start_screen);
+        // Also throw away anything typed into the bars while on the start screen.
+        app.add_systems(OnExit(AppState::StartScreen), reset_words);
 
         app.add_systems(OnEnter(AppState::EndScreen), end_screen);
     }
 }
 
-fn start_screen(
-    mut commands: Commands,
-    gltf_assets: Res<GltfAssets>,
-    font_assets: Res<FontAssets>,
-) {
-    // rival
-
+fn start_portrait(mut commands: Commands, gltf_assets: Res<GltfAssets>) {
     commands.spawn((
         SceneRoot(gltf_assets.birb_gold.clone()),
         Transform::from_xyz(8.4, 4.0, -0.2)
@@ -51,9 +73,13 @@ fn start_screen(
         Name::new("RivalPortrait"),
         DespawnOnExit(AppState::StartScreen),
     ));
+}
 
-    // text
-
+fn start_screen(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    rival_progress: Res<RivalProgress>,
+) {
     let container = commands
         .spawn((
             Node {
@@ -67,7 +93,7 @@ fn start_screen(
                 flex_direction: FlexDirection::Column,
                 ..default()
             },
-            DespawnOnExit(AppState::StartScreen),
+            DespawnOnExit(StartMenu::Main),
         ))
         .id();
 
@@ -86,12 +112,22 @@ fn start_screen(
         ))
         .id();
 
+    let rival = &RIVALS[rival_progress.next_rival()];
+
     let start_text = commands
         .spawn((
-            Text::new(concat!(
-                "So you want to join the flock, eh?\n",
-                "You'll have to beat me first!\n",
-                "Type the word below when you're ready."
+            Text::new(format!("{}\n", rival.name.to_uppercase())),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::srgb_u8(255, 235, 146)),
+        ))
+        .with_child((
+            TextSpan::new(format!(
+                "{}\nType the word below when you're ready.",
+                rival.greeting
             )),
             TextFont {
                 font: font_assets.main.clone(),
@@ -124,42 +160,57 @@ fn start_screen(
         ))
         .id();
 
+    let settings_target = commands
+        .spawn((
+            Text::default(),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(LIME.into()),
+            TypingTarget::new_whole("settings".into(), vec![Action::Settings]),
+        ))
+        .with_child((
+            TextSpan::new("SETTINGS"),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::srgb_u8(255, 235, 146)),
+        ))
+        .id();
+
+    let targets = commands
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::SpaceBetween,
+            ..default()
+        })
+        .id();
+
     commands.entity(container).add_children(&[bg]);
+    commands.entity(bg).add_children(&[start_text, targets]);
     commands
-        .entity(bg)
-        .add_children(&[start_text, start_target]);
+        .entity(targets)
+        .add_children(&[start_target, settings_target]);
 }
 
 fn end_screen(
     mut commands: Commands,
     gltf_assets: Res<GltfAssets>,
     font_assets: Res<FontAssets>,
-    score: Res<Score>,
+    current_rival: Res<CurrentRival>,
+    crashed_rival_query: Query<(), (With<Rival>, With<Crashed>)>,
+    stats: Res<RunStats>,
+    difficulty: Res<ActiveDifficulty>,
 ) {
-    let death_msg = if score.0 > 1000 {
-        concat!(
-            "I... wha... wow!\n,
-            What am I even doing with my life?\n",
-            "The flock is yours, if you'll have us!"
-        )
-    } else if score.0 > 400 {
-        concat!(
-            "That was a close one!\n",
-            "With moves like that, you'll\n",
-            "fit in well here!"
-        )
-    } else if score.0 > 200 {
-        concat!(
-            "Not bad, kid!\n",
-            "There may be room for you in the flock\n",
-            "as an unpaid apprentice."
-        )
+    let rival = current_rival.profile();
+    let death_msg = if crashed_rival_query.is_empty() {
+        rival.taunt
     } else {
-        concat!(
-            "Oh wow, ouch!\n",
-            "It's a shame you can't move side to side,\n",
-            "the path is a bit clearer over here!"
-        )
+        rival.concession
     };
 
     // rival
@@ -210,7 +261,37 @@ fn end_screen(
 
     let dead_text = commands
         .spawn((
-            Text::new(death_msg),
+            Text::new(format!("{}\n", rival.name.to_uppercase())),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::srgb_u8(255, 235, 146)),
+        ))
+        .with_child((
+            TextSpan::new(death_msg),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ))
+        .id();
+
+    let mut stats_line = format!(
+        "{:.0} WPM  {:.0}% ACCURACY",
+        stats.wpm(),
+        stats.accuracy() * 100.
+    );
+    if difficulty.adaptive {
+        stats_line += &format!("  PACE {:+.0}%", difficulty.adjustment * 100.);
+    }
+
+    let stats_text = commands
+        .spawn((
+            Text::new(stats_line),
             TextFont {
                 font: font_assets.main.clone(),
                 font_size: FONT_SIZE,
@@ -242,8 +323,33 @@ fn end_screen(
         ))
         .id();
 
+    // Flies the course again, against the ghost of the best run on it.
+    let race_text = commands
+        .spawn((
+            Text::default(),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(LIME.into()),
+            TypingTarget::new_whole("race".into(), vec![Action::Race]),
+        ))
+        .with_child((
+            TextSpan::new("RACE"),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::srgb_u8(255, 235, 146)),
+        ))
+        .id();
+
     commands.entity(container).add_children(&[bg]);
-    commands.entity(bg).add_children(&[dead_text, retry_text]);
+    commands
+        .entity(bg)
+        .add_children(&[dead_text, stats_text, retry_text, race_text]);
 }
 
 fn update_score(mut query: Query<&mut TextSpan, With<ScoreText>>, score: Res<Score>) {
@@ -264,6 +370,51 @@ fn update_high_score(mut query: Query<&mut TextSpan, With<HighScoreText>>, score
     }
 }
 
+fn update_rival_lead(
+    mut query: Query<&mut TextSpan, With<RivalLeadText>>,
+    rival_score: Res<RivalScore>,
+    score: Res<Score>,
+) {
+    if !rival_score.is_changed() && !score.is_changed() {
+        return;
+    }
+    for mut text in query.iter_mut() {
+        let lead = rival_score.lead(score.0);
+        text.0 = match lead.cmp(&0) {
+            Ordering::Greater => format!("{lead} PTS AHEAD"),
+            Ordering::Less => format!("{} PTS BEHIND", -lead),
+            Ordering::Equal => "EVEN".to_string(),
+        };
+    }
+}
+
+fn update_power_ups(mut query: Query<&mut Text, With<PowerUpText>>, active: Res<ActivePowerUps>) {
+    if !active.is_changed() {
+        return;
+    }
+
+    let timed = [
+        (PowerUp::SlowMotion, &active.slow_motion),
+        (PowerUp::Magnet, &active.magnet),
+        (PowerUp::DoubleScore, &active.double_score),
+    ];
+
+    let mut parts: Vec<String> = timed
+        .iter()
+        .filter_map(|(kind, timer)| {
+            let timer = timer.as_ref()?;
+            Some(format!("{} {:.1}", kind.label(), timer.remaining_secs()))
+        })
+        .collect();
+    if active.shield {
+        parts.push(PowerUp::Shield.label().to_string());
+    }
+
+    for mut text in query.iter_mut() {
+        text.0 = parts.join("  ");
+    }
+}
+
 fn update_targets(
     query: Query<(Entity, &TypingTarget), Changed<TypingTarget>>,
     mut writer: TextUiWriter,
@@ -276,6 +427,27 @@ fn update_targets(
     }
 }
 
+/// Deals fresh words to the top and bottom bars from a list seeded with the current [`Seed`], so
+/// that every run on a seed types the same words.
+fn reset_words(
+    mut word_list: ResMut<WordList>,
+    mut query: Query<&mut TypingTarget>,
+    seed: Res<Seed>,
+) {
+    *word_list = WordList::seeded(seed.0);
+
+    let mut used: HashSet<char> = START_MENU_WORDS.iter().flat_map(|w| w.chars()).collect();
+
+    for mut target in query
+        .iter_mut()
+        .filter(|target| !target.letter_actions.is_empty())
+    {
+        let word = word_list.find_next_word(&used);
+        used.extend(word.chars());
+        target.replace(word);
+    }
+}
+
 fn setup(
     mut commands: Commands,
     mut word_list: ResMut<WordList>,
@@ -314,7 +486,7 @@ fn setup(
         ))
         .id();
 
-    let mut used: HashSet<char> = "start".chars().collect();
+    let mut used: HashSet<char> = START_MENU_WORDS.iter().flat_map(|w| w.chars()).collect();
 
     let top_word = word_list.find_next_word(&used);
     for c in top_word.chars() {
@@ -456,11 +628,62 @@ fn setup(
         ))
         .id();
 
+    let rival_lead_text = commands
+        .spawn((
+            Text::new("RIVAL "),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::srgba(0.8, 0.8, 0.8, 1.0)),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(3.0),
+                left: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+        ))
+        .with_child((
+            TextSpan::new(""),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            RivalLeadText,
+        ))
+        .id();
+
+    let power_up_text = commands
+        .spawn((
+            Text::default(),
+            TextFont {
+                font: font_assets.main.clone(),
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::srgb_u8(255, 235, 146)),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(3.0),
+                right: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            PowerUpText,
+        ))
+        .id();
+
     commands.entity(root).add_children(&[top_bar, bottom_bar]);
     commands
         .entity(top_bar)
         .add_children(&[top_text, score_text, high_score_text]);
-    commands.entity(bottom_bar).add_children(&[bottom_text]);
+    commands
+        .entity(bottom_bar)
+        .add_children(&[bottom_text, rival_lead_text, power_up_text]);
 }
 
 fn decorate_rival_portrait(
@@ -0,0 +1,382 @@
+//! Weather events that break up long runs: gusts of wind that push the birb around, rain that
+//! hides upcoming gaps in fog and snow that weighs the birb down.
+//!
+//! Events are scheduled from the run's seed, so runs that share a seed share their weather. The
+//! rival flies through the same weather as the birb.
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::{
+    luck::Seed, movement, rival::Rival, AppState, Birb, FontAssets, InRun, Speed, TargetPosition,
+    BIRB_MAX_Y, BIRB_MIN_Y,
+};
+
+/// How long the weather stays clear between events.
+const CALM_SECS: std::ops::Range<f32> = 20.0..40.;
+const EVENT_SECS: std::ops::Range<f32> = 10.0..18.;
+
+/// How hard the strongest gusts push the birb up or down, in units per second.
+const WIND_STRENGTH: f32 = 0.6;
+/// How many gusts blow per second.
+const GUST_RATE: f32 = 0.8;
+/// How quickly snow drags the birb down, in units per second.
+const SNOW_WEIGHT: f32 = 0.15;
+
+/// How quickly the fog closes in or clears up.
+const FOG_RATE: f32 = 0.5;
+const CLEAR_FOG: (f32, f32) = (40., 90.);
+const RAIN_FOG: (f32, f32) = (8., 35.);
+const SNOW_FOG: (f32, f32) = (15., 55.);
+
+const RAIN_DROPS: usize = 160;
+const SNOWFLAKES: usize = 120;
+/// The region around the flight lane that rain and snow fall through.
+const PRECIPITATION_MIN: Vec3 = Vec3::new(-12., 0., -6.);
+const PRECIPITATION_MAX: Vec3 = Vec3::new(30., 12., 8.);
+
+const BANNER_SECS: f32 = 2.5;
+
+pub struct WeatherPlugin;
+
+/// Whether weather events happen, as set in the settings.
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeatherEvents(pub bool);
+impl Default for WeatherEvents {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeatherKind {
+    #[default]
+    Clear,
+    Wind,
+    Rain,
+    Snow,
+}
+impl WeatherKind {
+    const EVENTS: [WeatherKind; 3] = [WeatherKind::Wind, WeatherKind::Rain, WeatherKind::Snow];
+
+    fn announcement(&self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "CLEARING UP",
+            WeatherKind::Wind => "WIND GUSTS",
+            WeatherKind::Rain => "RAIN",
+            WeatherKind::Snow => "SNOW",
+        }
+    }
+
+    fn fog(&self) -> (f32, f32) {
+        match self {
+            WeatherKind::Clear | WeatherKind::Wind => CLEAR_FOG,
+            WeatherKind::Rain => RAIN_FOG,
+            WeatherKind::Snow => SNOW_FOG,
+        }
+    }
+}
+
+/// The weather of the current run, and when it changes next.
+#[derive(Resource)]
+pub struct Weather {
+    /// Whether the run has weather events at all.
+    pub enabled: bool,
+    pub kind: WeatherKind,
+    timer: Timer,
+    /// Whether gusts start out blowing up or down.
+    wind_direction: f32,
+    rng: StdRng,
+}
+impl Weather {
+    fn new(seed: &Seed, enabled: bool) -> Self {
+        // Offset the seed so that the weather doesn't follow the same sequence as the gaps.
+        let mut rng = StdRng::seed_from_u64(seed.0.wrapping_add(0x5eed));
+        let calm = rng.random_range(CALM_SECS);
+
+        Self {
+            enabled,
+            kind: WeatherKind::Clear,
+            timer: Timer::from_seconds(calm, TimerMode::Once),
+            wind_direction: 1.,
+            rng,
+        }
+    }
+}
+impl Default for Weather {
+    fn default() -> Self {
+        Self::new(&Seed(0), false)
+    }
+}
+
+#[derive(Component)]
+struct Precipitation {
+    velocity: Vec3,
+}
+
+#[derive(Component)]
+struct WeatherBanner(Timer);
+
+#[derive(Resource)]
+struct PrecipitationAssets {
+    drop: Handle<Mesh>,
+    flake: Handle<Mesh>,
+    drop_material: Handle<StandardMaterial>,
+    flake_material: Handle<StandardMaterial>,
+}
+impl FromWorld for PrecipitationAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let drop = meshes.add(Cuboid::new(0.02, 0.35, 0.02));
+        let flake = meshes.add(Sphere::new(0.05).mesh().ico(0).unwrap());
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let drop_material = materials.add(StandardMaterial {
+            base_color: Color::srgba(0.7, 0.8, 1., 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
+        let flake_material = materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            ..default()
+        });
+
+        Self {
+            drop,
+            flake,
+            drop_material,
+            flake_material,
+        }
+    }
+}
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weather>();
+        app.init_resource::<PrecipitationAssets>();
+
+        app.add_systems(OnExit(AppState::StartScreen), apply_weather);
+        app.add_systems(OnExit(InRun), clear_weather);
+        app.add_systems(
+            Update,
+            (change_weather, (wind, snow_weight).before(movement))
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        );
+        app.add_systems(
+            Update,
+            precipitation.run_if(in_state(AppState::Playing).or(in_state(AppState::Crashing))),
+        );
+        app.add_systems(Update, (weather_fog, fade_banner));
+    }
+}
+
+pub fn apply_weather(mut commands: Commands, seed: Res<Seed>, events: Res<WeatherEvents>) {
+    start_weather(&mut commands, &seed, events.0);
+}
+
+/// Schedules the weather for a run played with `seed`.
+pub fn start_weather(commands: &mut Commands, seed: &Seed, enabled: bool) {
+    commands.insert_resource(Weather::new(seed, enabled));
+}
+
+fn clear_weather(mut weather: ResMut<Weather>) {
+    weather.kind = WeatherKind::Clear;
+}
+
+fn change_weather(
+    mut commands: Commands,
+    mut weather: ResMut<Weather>,
+    precipitation_query: Query<(), With<Precipitation>>,
+    assets: Res<PrecipitationAssets>,
+    font_assets: Res<FontAssets>,
+    time: Res<Time>,
+) {
+    if !weather.enabled || !weather.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let weather = &mut *weather;
+
+    let (kind, secs) = if weather.kind == WeatherKind::Clear {
+        (
+            *WeatherKind::EVENTS.choose(&mut weather.rng).unwrap(),
+            weather.rng.random_range(EVENT_SECS),
+        )
+    } else {
+        (WeatherKind::Clear, weather.rng.random_range(CALM_SECS))
+    };
+
+    weather.kind = kind;
+    weather.timer = Timer::from_seconds(secs, TimerMode::Once);
+    weather.wind_direction = if weather.rng.random_bool(0.5) {
+        1.
+    } else {
+        -1.
+    };
+
+    commands.spawn((
+        Text::new(kind.announcement()),
+        TextFont {
+            font: font_assets.main.clone(),
+            font_size: 48.,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextShadow::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(18.),
+            width: Val::Percent(100.),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TextLayout::new_with_justify(Justify::Center),
+        WeatherBanner(Timer::from_seconds(BANNER_SECS, TimerMode::Once)),
+        Name::new("WeatherBanner"),
+        DespawnOnExit(InRun),
+    ));
+
+    // Rain and snow start falling all at once from above, and stop by falling out of the sky.
+    let (mesh, material, count) = match kind {
+        WeatherKind::Rain => (&assets.drop, &assets.drop_material, RAIN_DROPS),
+        WeatherKind::Snow => (&assets.flake, &assets.flake_material, SNOWFLAKES),
+        _ => return,
+    };
+
+    if !precipitation_query.is_empty() {
+        return;
+    }
+
+    let mut rng = rand::rng();
+    for _ in 0..count {
+        let position = Vec3::new(
+            rng.random_range(PRECIPITATION_MIN.x..PRECIPITATION_MAX.x),
+            rng.random_range(PRECIPITATION_MAX.y..PRECIPITATION_MAX.y * 2.),
+            rng.random_range(PRECIPITATION_MIN.z..PRECIPITATION_MAX.z),
+        );
+
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(position),
+            Precipitation {
+                velocity: match kind {
+                    WeatherKind::Rain => Vec3::new(-1., -14., 0.) * rng.random_range(0.8..1.2),
+                    _ => Vec3::new(-0.3, -1.2, 0.) * rng.random_range(0.6..1.4),
+                },
+            },
+            Name::new("Precipitation"),
+            DespawnOnExit(InRun),
+        ));
+    }
+}
+
+/// Gusts of wind push the birb and its rival up or down.
+fn wind(
+    mut query: Query<&mut TargetPosition, Or<(With<Birb>, With<Rival>)>>,
+    weather: Res<Weather>,
+    time: Res<Time>,
+) {
+    if weather.kind != WeatherKind::Wind {
+        return;
+    }
+
+    // Gusts come and go over the course of the event.
+    let elapsed = weather.timer.elapsed_secs();
+    let gust = (elapsed * GUST_RATE * std::f32::consts::TAU).sin().max(0.);
+    let push = weather.wind_direction * gust * WIND_STRENGTH * time.delta_secs();
+
+    for mut target in query.iter_mut() {
+        target.0.y = (target.0.y + push).clamp(BIRB_MIN_Y, BIRB_MAX_Y);
+    }
+}
+
+/// Snow settles on the wings of the birb and its rival, slowly dragging them down.
+fn snow_weight(
+    mut query: Query<&mut TargetPosition, Or<(With<Birb>, With<Rival>)>>,
+    weather: Res<Weather>,
+    time: Res<Time>,
+) {
+    if weather.kind != WeatherKind::Snow {
+        return;
+    }
+
+    for mut target in query.iter_mut() {
+        target.0.y = (target.0.y - SNOW_WEIGHT * time.delta_secs()).max(BIRB_MIN_Y);
+    }
+}
+
+/// Moves rain and snow, recycling it at the top while it's still falling.
+fn precipitation(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &Precipitation)>,
+    weather: Res<Weather>,
+    speed: Res<Speed>,
+    time: Res<Time>,
+) {
+    let falling = matches!(weather.kind, WeatherKind::Rain | WeatherKind::Snow);
+    // The birb flies through the weather, so it streams past along with the ground.
+    let scroll = Vec3::X * -speed.current;
+
+    for (entity, mut transform, precipitation) in query.iter_mut() {
+        transform.translation += (precipitation.velocity + scroll) * time.delta_secs();
+
+        if transform.translation.y > PRECIPITATION_MIN.y
+            && transform.translation.x > PRECIPITATION_MIN.x
+        {
+            continue;
+        }
+
+        if !falling {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        if transform.translation.y <= PRECIPITATION_MIN.y {
+            transform.translation.y += PRECIPITATION_MAX.y;
+        }
+        if transform.translation.x <= PRECIPITATION_MIN.x {
+            transform.translation.x += PRECIPITATION_MAX.x - PRECIPITATION_MIN.x;
+        }
+    }
+}
+
+/// Rain and snow close the fog in around the birb.
+fn weather_fog(mut query: Query<&mut DistanceFog>, weather: Res<Weather>, time: Res<Time<Real>>) {
+    let (start, end) = weather.kind.fog();
+    let t = 1. - (-FOG_RATE * time.delta_secs()).exp();
+
+    for mut fog in query.iter_mut() {
+        let FogFalloff::Linear {
+            start: current_start,
+            end: current_end,
+        } = &mut fog.falloff
+        else {
+            continue;
+        };
+
+        *current_start = current_start.lerp(start, t);
+        *current_end = current_end.lerp(end, t);
+    }
+}
+
+fn fade_banner(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut TextColor, &mut WeatherBanner)>,
+    time: Res<Time>,
+) {
+    for (entity, mut color, mut banner) in query.iter_mut() {
+        if banner.0.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // Hold for a moment before fading away.
+        color
+            .0
+            .set_alpha((banner.0.fraction_remaining() * 2.).min(1.));
+    }
+}